@@ -0,0 +1,185 @@
+use std::fmt::Display;
+
+use crate::{policy::SafetyPolicy, Direction, Level};
+
+/// What happened to a report once it was assessed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Safe as it is
+    Safe,
+    /// Safe once the Problem Dampener removed some levels
+    Dampened,
+    Unsafe,
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verdict = match self {
+            Verdict::Safe => "safe",
+            Verdict::Dampened => "dampened",
+            Verdict::Unsafe => "unsafe",
+        };
+        f.pad(verdict)
+    }
+}
+
+/// The rule a pair of adjacent levels breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// The levels moved against the direction of the report
+    WrongDirection,
+    /// Both levels are the same and plateaus are not allowed
+    Plateau,
+    /// The levels moved less than the minimum step
+    StepTooSmall,
+    /// The levels moved more than the maximum step
+    StepTooLarge,
+}
+
+impl Rule {
+    fn broken_by(
+        policy: &SafetyPolicy,
+        direction: &Direction,
+        previous: Level,
+        next: Level,
+    ) -> Option<Self> {
+        if policy.is_safe_step(direction, previous, next) {
+            return None;
+        }
+        let delta = direction.step(previous, next);
+        let rule = if delta < 0 {
+            Rule::WrongDirection
+        } else if delta == 0 {
+            Rule::Plateau
+        } else if delta < policy.min_step as i128 {
+            Rule::StepTooSmall
+        } else {
+            Rule::StepTooLarge
+        };
+        Some(rule)
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rule = match self {
+            Rule::WrongDirection => "wrong direction",
+            Rule::Plateau => "plateau",
+            Rule::StepTooSmall => "step too small",
+            Rule::StepTooLarge => "step too large",
+        };
+        write!(f, "{}", rule)
+    }
+}
+
+/// First pair of adjacent levels in the original report that is not safe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    /// Index of the first level of the pair
+    pub index: usize,
+    pub previous: Level,
+    pub next: Level,
+    pub direction: Direction,
+    pub rule: Rule,
+}
+
+/// Full result of assessing a report, with the reason it's unsafe and the
+/// levels the Problem Dampener removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assessment {
+    pub verdict: Verdict,
+    pub violation: Option<Violation>,
+    /// Indices of the levels removed to make the report safe
+    pub removed: Vec<usize>,
+}
+
+impl Assessment {
+    pub fn new(report: &[Level], policy: &SafetyPolicy) -> Self {
+        let violation = Self::first_violation(report, policy);
+        if violation.is_none() {
+            return Self {
+                verdict: Verdict::Safe,
+                violation,
+                removed: Vec::new(),
+            };
+        }
+        let removed = policy
+            .directions
+            .candidates()
+            .iter()
+            .filter_map(|direction| direction.dampen(report, policy))
+            .min_by_key(|removed| removed.len());
+        match removed {
+            Some(removed) => Self {
+                verdict: Verdict::Dampened,
+                violation,
+                removed,
+            },
+            None => Self {
+                verdict: Verdict::Unsafe,
+                violation,
+                removed: Vec::new(),
+            },
+        }
+    }
+
+    /// Picks the direction the report holds for the longest and returns the
+    /// pair that breaks it
+    fn first_violation(report: &[Level], policy: &SafetyPolicy) -> Option<Violation> {
+        let violations = policy
+            .directions
+            .candidates()
+            .iter()
+            .map(|direction| {
+                report.windows(2).enumerate().find_map(|(index, pair)| {
+                    let rule = Rule::broken_by(policy, direction, pair[0], pair[1])?;
+                    Some(Violation {
+                        index,
+                        previous: pair[0],
+                        next: pair[1],
+                        direction: *direction,
+                        rule,
+                    })
+                })
+            })
+            .collect::<Vec<Option<Violation>>>();
+        if violations.iter().any(|violation| violation.is_none()) {
+            return None;
+        }
+        violations
+            .into_iter()
+            .flatten()
+            .max_by_key(|violation| violation.index)
+    }
+
+    /// One line summary of the assessment, meant for `--explain`
+    pub fn explain(&self, report: &[Level]) -> String {
+        let levels = report
+            .iter()
+            .map(|level| level.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        let mut line = format!("{:<8} [{}]", self.verdict, levels);
+        if let Some(violation) = self.violation {
+            line.push_str(&format!(
+                " {} -> {} at levels {}..{}: {} while {}",
+                violation.previous,
+                violation.next,
+                violation.index,
+                violation.index + 1,
+                violation.rule,
+                format!("{:?}", violation.direction).to_lowercase(),
+            ));
+        }
+        if !self.removed.is_empty() {
+            let removed = self
+                .removed
+                .iter()
+                .map(|index| format!("{} ({})", index, report[*index]))
+                .collect::<Vec<String>>()
+                .join(", ");
+            line.push_str(&format!(", fixed by removing {}", removed));
+        }
+        line
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use crate::{policy::SafetyPolicy, render_bars, Level, Report};
+
+/// How far a report is from being safe, ignoring the policy tolerance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distance {
+    /// Fewest levels to remove to make the report safe
+    pub removals: usize,
+    /// Longest safe subsequence left after those removals
+    pub subsequence: Report,
+}
+
+impl Distance {
+    /// Runs the same dynamic program as the Problem Dampener but lets it
+    /// remove any number of levels, which is just as fast
+    pub fn new(report: &[Level], policy: &SafetyPolicy) -> Self {
+        let unbounded = SafetyPolicy {
+            tolerance: report.len(),
+            ..policy.clone()
+        };
+        let removed = unbounded
+            .directions
+            .candidates()
+            .iter()
+            .filter_map(|direction| direction.dampen(report, &unbounded))
+            .min_by_key(|removed| removed.len())
+            // A single level is always safe, so this only happens when the
+            // report is empty
+            .unwrap_or_default();
+        let mut kept = vec![true; report.len()];
+        for index in &removed {
+            kept[*index] = false;
+        }
+        let subsequence = report
+            .iter()
+            .zip(kept)
+            .filter(|(_, kept)| *kept)
+            .map(|(level, _)| *level)
+            .collect();
+        Self {
+            removals: removed.len(),
+            subsequence,
+        }
+    }
+}
+
+/// Counts how many reports need each number of removals
+pub fn histogram(distances: &[Distance]) -> BTreeMap<usize, usize> {
+    let mut histogram = BTreeMap::new();
+    for distance in distances {
+        *histogram.entry(distance.removals).or_insert(0) += 1;
+    }
+    histogram
+}
+
+/// Renders the histogram with one bar per number of removals, scaled to
+/// `width` characters
+pub fn render_histogram(histogram: &BTreeMap<usize, usize>, width: usize) -> String {
+    let rows: Vec<(String, usize)> = histogram
+        .iter()
+        .map(|(removals, count)| (format!("{} removed", removals), *count))
+        .collect();
+    render_bars(&rows, width)
+}
@@ -0,0 +1,487 @@
+pub mod assessment;
+pub mod distance;
+pub mod pipeline;
+pub mod policy;
+pub mod stats;
+
+use std::collections::HashMap;
+
+use policy::SafetyPolicy;
+
+pub type Level = i64;
+
+pub type Report = Vec<Level>;
+
+/// Parses one report per line, levels separated by spaces. Blank lines are
+/// skipped.
+pub fn parse_reports(input: &str) -> Vec<Report> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut levels = Report::new();
+            parse_levels_into(line, &mut levels);
+            levels
+        })
+        .collect()
+}
+
+/// Parses a single report into `levels`, reusing its allocation
+pub fn parse_levels_into(line: &str, levels: &mut Report) {
+    levels.clear();
+    levels.extend(
+        line.split_ascii_whitespace()
+            .map(|level| level.parse::<Level>().unwrap()),
+    );
+}
+
+/// Renders one labelled bar per row, scaled so the largest count takes
+/// `width` characters
+pub fn render_bars(rows: &[(String, usize)], width: usize) -> String {
+    let largest = rows
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    rows.iter()
+        .map(|(label, count)| {
+            let bar = "#".repeat((count * width).div_ceil(largest));
+            format!("{:>label_width$}: {:>6} {}", label, count, bar)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Safety {
+    Safe,
+    Unsafe,
+}
+
+impl From<bool> for Safety {
+    fn from(value: bool) -> Self {
+        if value {
+            Self::Safe
+        } else {
+            Self::Unsafe
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Direction {
+    Decreasing,
+    Increasing,
+}
+
+impl Safety {
+    /// Assesses a report allowing up to `policy.tolerance` levels to be
+    /// removed.
+    ///
+    /// Tries every direction the policy allows and, for each one, walks the
+    /// report keeping the fewest removals needed for a safe subsequence
+    /// ending at each level. The best level to follow is looked up by value,
+    /// once for each allowed step, so this is exact and takes
+    /// O(n * (max_step - min_step + 1)) whatever the tolerance.
+    pub fn assess(report: &[Level], policy: &SafetyPolicy) -> Self {
+        if report.len() <= policy.tolerance + 1 {
+            // Removing all but one level always leaves a safe report
+            return Safety::Safe;
+        }
+        // Most reports need no dampening, so check that without allocating
+        let already_safe = policy.directions.candidates().iter().any(|direction| {
+            report
+                .windows(2)
+                .all(|pair| policy.is_safe_step(direction, pair[0], pair[1]))
+        });
+        if already_safe {
+            return Safety::Safe;
+        }
+        let safe = policy
+            .directions
+            .candidates()
+            .iter()
+            .any(|direction| direction.removals_needed(report, policy).is_some());
+        Safety::from(safe)
+    }
+}
+
+impl Direction {
+    /// Signed size of the step from `previous` to `next` in this direction,
+    /// widened so levels at opposite ends of `Level` cannot overflow
+    pub fn step(&self, previous: Level, next: Level) -> i128 {
+        match self {
+            Direction::Decreasing => previous as i128 - next as i128,
+            Direction::Increasing => next as i128 - previous as i128,
+        }
+    }
+
+    /// Minimum number of levels (at most `policy.tolerance`) to remove from
+    /// the report so it is safe in this direction
+    fn removals_needed(&self, report: &[Level], policy: &SafetyPolicy) -> Option<usize> {
+        self.dampen(report, policy).map(|removed| removed.len())
+    }
+
+    /// Indices of the fewest levels (at most `policy.tolerance`) to remove
+    /// from the report so it is safe in this direction
+    pub fn dampen(&self, report: &[Level], policy: &SafetyPolicy) -> Option<Vec<usize>> {
+        let tolerance = policy.tolerance;
+        // best[i] is the fewest removals needed for a safe subsequence that
+        // keeps level i as its last level, and the kept level before it
+        let mut best: Vec<Option<(usize, Option<usize>)>> = vec![None; report.len()];
+        // Keeping level i right after level j removes everything between
+        // them, so it costs best[j] - j + i - 1. For every level value this
+        // keeps the kept level with the smallest best[j] - j, the latest one
+        // on ties.
+        let mut by_level: HashMap<Level, (i64, usize)> = HashMap::new();
+        let steps = policy.min_step..=policy.max_step;
+        let plateau = (policy.directions.allows_plateaus() && !steps.contains(&0)).then_some(0);
+        let step_count = policy
+            .max_step
+            .saturating_sub(policy.min_step)
+            .saturating_add(1)
+            .max(0) as u64;
+        for (index, level) in report.iter().enumerate() {
+            // Start the subsequence here by dropping every level before it
+            let mut current = (index <= tolerance).then_some((index, None));
+            let mut follow = |(score, previous): (i64, usize)| {
+                let removed = score + index as i64 - 1;
+                if removed > tolerance as i64 {
+                    return;
+                }
+                let removed = removed as usize;
+                // Ties go to the closest level, like they would walking back
+                let closer = |(fewest, before): (usize, Option<usize>)| {
+                    removed < fewest
+                        || (removed == fewest && before.is_some_and(|before| previous > before))
+                };
+                if current.is_none_or(closer) {
+                    current = Some((removed, Some(previous)));
+                }
+            };
+            if step_count <= by_level.len() as u64 {
+                // Only the values a safe step could have come from
+                for step in plateau.into_iter().chain(steps.clone()) {
+                    let previous = match self {
+                        Direction::Increasing => level.checked_sub(step),
+                        Direction::Decreasing => level.checked_add(step),
+                    };
+                    if let Some(candidate) = previous.and_then(|value| by_level.get(&value)) {
+                        follow(*candidate);
+                    }
+                }
+            } else {
+                // Huge step ranges would mean more lookups than levels seen
+                // so far, so check those instead
+                for (value, candidate) in &by_level {
+                    if policy.is_safe_step(self, *value, *level) {
+                        follow(*candidate);
+                    }
+                }
+            }
+            best[index] = current;
+            if let Some((removed, _)) = current {
+                let score = removed as i64 - index as i64;
+                by_level
+                    .entry(*level)
+                    .and_modify(|entry| {
+                        if score <= entry.0 {
+                            *entry = (score, index);
+                        }
+                    })
+                    .or_insert((score, index));
+            }
+        }
+        // End the subsequence by dropping every level after the last kept one
+        let (_, last) = best
+            .iter()
+            .enumerate()
+            .filter_map(|(last, best)| {
+                best.map(|(before, _)| (before + report.len() - last - 1, last))
+            })
+            .filter(|(total, _)| *total <= tolerance)
+            .min()?;
+        let mut kept = vec![false; report.len()];
+        let mut index = Some(last);
+        while let Some(current) = index {
+            kept[current] = true;
+            index = best[current].and_then(|(_, previous)| previous);
+        }
+        Some((0..report.len()).filter(|index| !kept[*index]).collect())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assessment::{Assessment, Rule, Verdict};
+    use policy::PolicyOverrides;
+
+    /// Pseudo-random numbers from `seed` for generating reports
+    fn numbers(mut seed: u64) -> impl FnMut() -> u64 {
+        move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        }
+    }
+
+    /// Checks every way of removing up to `policy.tolerance` levels
+    fn brute_force(report: &[Level], policy: &SafetyPolicy) -> Safety {
+        fn is_safe(report: &[Level], policy: &SafetyPolicy) -> bool {
+            policy.directions.candidates().iter().any(|direction| {
+                report
+                    .windows(2)
+                    .all(|pair| policy.is_safe_step(direction, pair[0], pair[1]))
+            })
+        }
+        fn search(report: &[Level], policy: &SafetyPolicy, tolerance: usize) -> bool {
+            if is_safe(report, policy) {
+                return true;
+            }
+            if tolerance == 0 {
+                return false;
+            }
+            (0..report.len()).any(|index| {
+                let mut skewed_report = report.to_vec();
+                skewed_report.remove(index);
+                search(&skewed_report, policy, tolerance - 1)
+            })
+        }
+        Safety::from(search(report, policy, policy.tolerance))
+    }
+
+    #[test]
+    fn example_reports() {
+        let reports = [
+            (vec![7, 6, 4, 2, 1], Safety::Safe, Safety::Safe),
+            (vec![1, 2, 7, 8, 9], Safety::Unsafe, Safety::Unsafe),
+            (vec![9, 7, 6, 2, 1], Safety::Unsafe, Safety::Unsafe),
+            (vec![1, 3, 2, 4, 5], Safety::Unsafe, Safety::Safe),
+            (vec![8, 6, 4, 4, 1], Safety::Unsafe, Safety::Safe),
+            (vec![1, 3, 6, 7, 9], Safety::Safe, Safety::Safe),
+        ];
+        for (report, part1, part2) in reports {
+            let policy = SafetyPolicy::with_tolerance(0);
+            assert_eq!(Safety::assess(&report, &policy), part1, "{:?}", report);
+            let policy = SafetyPolicy::with_tolerance(1);
+            assert_eq!(Safety::assess(&report, &policy), part2, "{:?}", report);
+        }
+    }
+
+    #[test]
+    fn removes_first_level_and_the_one_after_a_bad_step() {
+        let assess = |report: &[Level], tolerance| {
+            Safety::assess(report, &SafetyPolicy::with_tolerance(tolerance))
+        };
+        assert_eq!(assess(&[9, 1, 2, 3], 1), Safety::Safe);
+        assert_eq!(assess(&[1, 2, 3, 10, 4], 1), Safety::Safe);
+        assert_eq!(assess(&[5, 6, 4, 3, 2], 1), Safety::Safe);
+        assert_eq!(assess(&[1, 1, 1, 1], 3), Safety::Safe);
+        assert_eq!(assess(&[1, 1, 1, 1], 2), Safety::Unsafe);
+    }
+
+    #[test]
+    fn custom_policies() {
+        let policy =
+            PolicyOverrides::from_toml("min-step = 2\nmax-step = 5\ndirections = \"non-strict\"\n")
+                .unwrap();
+        let mut custom = SafetyPolicy::default();
+        policy.apply(&mut custom);
+        assert_eq!(Safety::assess(&[1, 3, 3, 8], &custom), Safety::Safe);
+        assert_eq!(Safety::assess(&[1, 2, 4], &custom), Safety::Unsafe);
+        custom.directions = policy::Directions::Increasing;
+        assert_eq!(Safety::assess(&[9, 7, 5], &custom), Safety::Unsafe);
+        assert_eq!(Safety::assess(&[5, 7, 9], &custom), Safety::Safe);
+        assert!(PolicyOverrides::from_toml("min_step = 2").is_err());
+        assert!(PolicyOverrides::from_toml("min-step = 0").is_err());
+        assert!(PolicyOverrides::from_toml("min-step = 3\nmax-step = 1").is_err());
+        // Each half is fine until both end up in the same policy
+        let mut policy = SafetyPolicy::default();
+        PolicyOverrides::from_toml("min-step = 4")
+            .unwrap()
+            .apply(&mut policy);
+        assert!(policy.validate().is_err());
+        policy.max_step = 6;
+        assert!(policy.validate().is_ok());
+    }
+
+    #[test]
+    fn explains_reports() {
+        let policy = SafetyPolicy::with_tolerance(1);
+        let assessment = Assessment::new(&[1, 3, 2, 4, 5], &policy);
+        assert_eq!(assessment.verdict, Verdict::Dampened);
+        assert_eq!(assessment.removed, vec![1]);
+        let violation = assessment.violation.unwrap();
+        assert_eq!((violation.index, violation.rule), (1, Rule::WrongDirection));
+        assert_eq!(violation.direction, Direction::Increasing);
+
+        let assessment = Assessment::new(&[9, 7, 6, 2, 1], &policy);
+        assert_eq!(assessment.verdict, Verdict::Unsafe);
+        let violation = assessment.violation.unwrap();
+        assert_eq!((violation.index, violation.rule), (2, Rule::StepTooLarge));
+
+        let assessment = Assessment::new(&[8, 6, 4, 4, 1], &policy);
+        assert_eq!(assessment.violation.unwrap().rule, Rule::Plateau);
+        assert_eq!(assessment.removed, vec![2]);
+
+        let assessment = Assessment::new(&[7, 6, 4, 2, 1], &policy);
+        assert_eq!(assessment.verdict, Verdict::Safe);
+        assert_eq!(assessment.violation, None);
+    }
+
+    #[test]
+    fn distance_is_the_smallest_tolerance_that_works() {
+        let policy = SafetyPolicy::default();
+        let distance = distance::Distance::new(&[1, 2, 7, 8, 9], &policy);
+        assert_eq!(distance.removals, 2);
+        assert_eq!(distance.subsequence, vec![7, 8, 9]);
+        for report in [vec![9, 7, 6, 2, 1], vec![5, 1, 9, 2, 3, 30, 4, 4, 5]] {
+            let distance = distance::Distance::new(&report, &policy);
+            let smallest = (0..report.len())
+                .find(|tolerance| {
+                    brute_force(&report, &SafetyPolicy::with_tolerance(*tolerance)) == Safety::Safe
+                })
+                .unwrap();
+            assert_eq!(distance.removals, smallest, "{:?}", report);
+            assert_eq!(distance.subsequence.len(), report.len() - smallest);
+            assert_eq!(Safety::assess(&distance.subsequence, &policy), Safety::Safe);
+        }
+    }
+
+    #[test]
+    fn trends_and_sparklines() {
+        use stats::{sparkline, Trend};
+        assert_eq!(
+            Trend::of(&[1, 2, 2, 5]),
+            Trend::Moving(Direction::Increasing)
+        );
+        assert_eq!(Trend::of(&[5, 5, 1]), Trend::Moving(Direction::Decreasing));
+        assert_eq!(Trend::of(&[3, 3]), Trend::Flat);
+        assert_eq!(Trend::of(&[1, 3, 2]), Trend::Mixed);
+        assert_eq!(sparkline(&[1, 8, 4, 4]), "▁█▄▄");
+        assert_eq!(sparkline(&[2, 2]), "▁▁");
+    }
+
+    #[test]
+    fn extreme_levels_do_not_overflow() {
+        let input = "9223372036854775807 -9223372036854775808";
+        let report = &parse_reports(input)[0];
+        let policy = SafetyPolicy::default();
+        assert_eq!(Safety::assess(report, &policy), Safety::Unsafe);
+        let violation = Assessment::new(report, &policy).violation.unwrap();
+        assert_eq!(violation.rule, Rule::StepTooLarge);
+
+        let stats = stats::Stats::new(std::slice::from_ref(report), &policy);
+        assert_eq!(
+            stats.deltas.keys().collect::<Vec<_>>(),
+            [&-(u64::MAX as i128)]
+        );
+        assert_eq!(stats::sparkline(report), "█▁");
+
+        let throughput = pipeline::assess_stream(input.as_bytes(), &policy, 1, 1).unwrap();
+        assert_eq!(throughput.safe_reports, 0);
+    }
+
+    #[test]
+    fn stream_matches_sequential() {
+        let mut next = numbers(0x0031);
+        let input = (0..3000)
+            .map(|_| {
+                let mut level = (next() % 50) as Level;
+                (0..(next() % 7 + 2))
+                    .map(|_| {
+                        level += (next() % 9) as Level - 4;
+                        level.to_string()
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        for tolerance in 0..=2 {
+            let policy = SafetyPolicy::with_tolerance(tolerance);
+            let sequential = parse_reports(&input)
+                .iter()
+                .filter(|report| Safety::assess(report, &policy) == Safety::Safe)
+                .count();
+            for (threads, chunk_size) in [(1, 0), (1, 1), (3, 7), (8, 1000)] {
+                let throughput =
+                    pipeline::assess_stream(input.as_bytes(), &policy, threads, chunk_size)
+                        .unwrap();
+                assert_eq!(throughput.reports, 3000);
+                assert_eq!(throughput.safe_reports, sequential);
+                assert_eq!(throughput.bytes, input.len());
+            }
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut next = numbers(0x2024_1202);
+        let policies = [
+            SafetyPolicy::default(),
+            SafetyPolicy {
+                directions: policy::Directions::NonStrict,
+                ..Default::default()
+            },
+            SafetyPolicy {
+                min_step: 2,
+                max_step: 4,
+                directions: policy::Directions::Decreasing,
+                ..Default::default()
+            },
+            // Wider than any report, so kept levels are scanned instead
+            SafetyPolicy {
+                max_step: 1000,
+                directions: policy::Directions::NonStrict,
+                ..Default::default()
+            },
+        ];
+        for _ in 0..2000 {
+            let length = (next() % 8) as usize + 1;
+            let mut level = (next() % 10) as Level;
+            let report: Report = (0..length)
+                .map(|_| {
+                    level += (next() % 9) as Level - 4;
+                    level
+                })
+                .collect();
+            for policy in policies.iter() {
+                for tolerance in 0..=3 {
+                    let policy = SafetyPolicy {
+                        tolerance,
+                        ..policy.clone()
+                    };
+                    let expected = brute_force(&report, &policy);
+                    assert_eq!(
+                        Safety::assess(&report, &policy),
+                        expected,
+                        "{:?} with {:?}",
+                        report,
+                        policy
+                    );
+                    // The dampened report has to be safe on its own
+                    let assessment = Assessment::new(&report, &policy);
+                    let dampened: Report = (0..report.len())
+                        .filter(|index| !assessment.removed.contains(index))
+                        .map(|index| report[index])
+                        .collect();
+                    let strict = SafetyPolicy {
+                        tolerance: 0,
+                        ..policy.clone()
+                    };
+                    assert_eq!(
+                        assessment.verdict != Verdict::Unsafe,
+                        expected == Safety::Safe
+                    );
+                    if assessment.verdict != Verdict::Unsafe {
+                        assert_eq!(brute_force(&dampened, &strict), Safety::Safe);
+                    }
+                }
+            }
+        }
+    }
+}
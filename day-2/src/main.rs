@@ -1,5 +1,17 @@
+use std::thread;
+
 use clap::Parser;
 
+use day_2::{
+    assessment::Assessment,
+    distance::{self, Distance},
+    parse_reports,
+    pipeline::assess_stream,
+    policy::{PolicyError, PolicyOverrides, SafetyPolicy},
+    stats::{sparkline, Stats},
+    Safety,
+};
+
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct Args {
@@ -9,6 +21,33 @@ struct Args {
     /// Solver
     #[arg(short, long, value_enum)]
     solver: Solver,
+    /// TOML file with the safety policy, flags take precedence over it
+    #[arg(short, long)]
+    config: Option<std::path::PathBuf>,
+    #[command(flatten)]
+    policy: PolicyOverrides,
+    /// Print why each report is safe or unsafe
+    #[arg(short, long)]
+    explain: bool,
+    /// Print how many levels each report is from being safe
+    #[arg(short, long)]
+    distances: bool,
+    /// Print distributions over all reports and a sparkline per report
+    #[arg(long)]
+    stats: bool,
+    /// Only draw sparklines for the first N reports
+    #[arg(long)]
+    sparklines: Option<usize>,
+    /// Stream the file through a thread pool instead of loading it whole,
+    /// only the safe count and throughput are printed
+    #[arg(long)]
+    stream: bool,
+    /// Threads for the streaming solver, defaults to every available core
+    #[arg(short, long)]
+    threads: Option<usize>,
+    /// Reports per chunk handed to each thread when streaming
+    #[arg(long, default_value_t = 65536)]
+    chunk_size: usize,
 }
 
 #[derive(Debug, Clone, clap::ValueEnum)]
@@ -17,171 +56,110 @@ enum Solver {
     Part2,
 }
 
-type Level = i64;
-
-type Report = Vec<Level>;
-
-#[derive(Debug, PartialEq, Eq)]
-enum Safety {
-    Safe,
-    Unsafe,
-}
-
-impl From<bool> for Safety {
-    fn from(value: bool) -> Self {
-        if value {
-            Self::Safe
-        } else {
-            Self::Unsafe
+impl Solver {
+    /// The policy from the puzzle, Part2 turns on the Problem Dampener
+    pub fn default_policy(&self) -> SafetyPolicy {
+        match self {
+            Solver::Part1 => SafetyPolicy::with_tolerance(0),
+            Solver::Part2 => SafetyPolicy::with_tolerance(1),
         }
     }
-}
 
-#[derive(Debug, PartialEq, Eq)]
-enum Direction {
-    Decreasing,
-    Stable,
-    Increasing,
+    pub fn solve(&self, input: String, policy: &SafetyPolicy) -> String {
+        let reports = parse_reports(&input);
+        let safety = reports
+            .iter()
+            .map(|report| Safety::assess(report, policy))
+            .fold(
+                0,
+                |acc, safety| if safety == Safety::Safe { acc + 1 } else { acc },
+            );
+        safety.to_string()
+    }
 }
 
-impl Safety {
-    pub fn assess(report: Report) -> Self {
-        // Conditions for safe:
-        // the levels are all increasing or decreasing
-        // the distance between levels has to be from 1 to 3
-        println!("{:?}", report);
-        let mut seq = report.iter().zip(report.iter().skip(1));
-        let increasing = seq.clone().all(|(a, b)| a < b);
-        let decreasing = seq.clone().all(|(a, b)| a > b);
-        let preliminary_safety = increasing || decreasing;
-        if !preliminary_safety {
-            println!("{:?} {:?}", increasing, decreasing);
-            return Safety::Unsafe;
-        }
-        let delta_safe = seq.all(|(a, b)| 1 <= (b - a).abs() && (b - a).abs() <= 3);
-        println!("{:?}", delta_safe);
-        Safety::from(delta_safe)
+fn main() {
+    let args = Args::parse();
+    let mut policy = args.solver.default_policy();
+    if let Some(config) = args.config {
+        let config = std::fs::read_to_string(config).unwrap();
+        PolicyOverrides::from_toml(&config)
+            .unwrap_or_else(|error| exit_with(error))
+            .apply(&mut policy);
     }
-
-    pub fn assess_with_tolerance(report: Report, tolerance: Option<i64>) -> Self {
-        // Conditions for safe:
-        // the levels are all increasing or decreasing
-        // the distance between levels has to be from 1 to 3
-        // if one level is bad, it's ok
-        println!("\n{:?}", report);
-        let seq = report.iter().zip(report.iter().skip(1));
-        let increasing = seq.clone().filter(|(a, b)| a < b).count();
-        let decreasing = seq.clone().filter(|(a, b)| a > b).count();
-        let (direction, preliminary_safety) = match increasing.cmp(&decreasing) {
-            std::cmp::Ordering::Less => {
-                // This means that the levels are decreasing
-                println!("Decreasing {} {}", increasing, decreasing);
-                (Direction::Decreasing, increasing <= 1)
-            }
-            std::cmp::Ordering::Equal => {
-                // This is fucked
-                (Direction::Stable, false)
-            }
-            std::cmp::Ordering::Greater => {
-                // This means that the levels are increasing
-                println!("Increasing {} {}", increasing, decreasing);
-                (Direction::Increasing, decreasing <= 1)
-            }
-        };
-        if !preliminary_safety {
-            println!("Preliminary Safety: {}", preliminary_safety);
-            return Safety::Unsafe;
+    args.policy.apply(&mut policy);
+    policy.validate().unwrap_or_else(|error| exit_with(error));
+    let input = args.path;
+    println!("{:?}", input);
+    if args.stream {
+        let threads = args
+            .threads
+            .unwrap_or_else(|| {
+                thread::available_parallelism()
+                    .map(|threads| threads.get())
+                    .unwrap_or(1)
+            })
+            .max(1);
+        let file = std::fs::File::open(input).unwrap();
+        let throughput = assess_stream(
+            std::io::BufReader::new(file),
+            &policy,
+            threads,
+            args.chunk_size,
+        )
+        .unwrap();
+        println!(
+            "Assessed {} reports on {} threads in {:?} ({:.0} reports/s, {:.1} MB/s)",
+            throughput.reports,
+            threads,
+            throughput.elapsed,
+            throughput.reports_per_second(),
+            throughput.megabytes_per_second()
+        );
+        println!("{}", throughput.safe_reports);
+        return;
+    }
+    let contents = std::fs::read_to_string(input).unwrap();
+    println!("{}", contents);
+    if args.explain {
+        for (index, report) in parse_reports(&contents).iter().enumerate() {
+            let assessment = Assessment::new(report, &policy);
+            println!("{:>4}: {}", index + 1, assessment.explain(report));
         }
-        let mut skewed_report = report.clone();
-        let mut enumerated_skewed_report: Vec<(usize, (i64, i64))> = skewed_report
+    }
+    if args.distances {
+        let reports = parse_reports(&contents);
+        let distances: Vec<Distance> = reports
             .iter()
-            .cloned()
-            .zip(skewed_report.iter().cloned().skip(1))
-            .enumerate()
+            .map(|report| Distance::new(report, &policy))
             .collect();
-        let mut tolerance = tolerance.unwrap_or(1);
-        let mut current_index = 0;
-        while let Some((index, (previous, next))) =
-            enumerated_skewed_report.get(current_index).cloned()
-        {
-            if 1 <= (next - previous).abs()
-                && (next - previous).abs() <= 3
-                && match direction {
-                    Direction::Decreasing => previous > next,
-                    Direction::Stable => false,
-                    Direction::Increasing => previous < next,
-                }
-            {
-                println!("Within delta range: {} {}", previous, next);
-                current_index += 1;
-                continue;
-            }
-            if tolerance < 0 {
-                break;
-            }
-            println!("Reducing tolerance: {} {}", previous, next);
-            tolerance -= 1;
-            current_index = 0;
-            let removed = skewed_report.remove(index);
+        for (index, distance) in distances.iter().enumerate() {
             println!(
-                "Removed report level: {} ({})\n - {:?}",
-                index, removed, skewed_report
+                "{:>4}: {} removed, longest safe {:?}",
+                index + 1,
+                distance.removals,
+                distance.subsequence
             );
-            enumerated_skewed_report = skewed_report
-                .iter()
-                .cloned()
-                .zip(skewed_report.iter().cloned().skip(1))
-                .enumerate()
-                .collect();
         }
-        println!("Tolerance {}", tolerance);
-        Safety::from(tolerance >= 0)
+        println!(
+            "{}",
+            distance::render_histogram(&distance::histogram(&distances), 40)
+        );
     }
-}
-
-impl Solver {
-    pub fn solve(&self, input: String) -> String {
-        match self {
-            Solver::Part1 => {
-                let reports: Vec<Report> = input
-                    .lines()
-                    .map(|line| line.split(' ').collect::<Vec<&str>>())
-                    .map(|report| report.iter().map(|level| level.parse().unwrap()).collect())
-                    .collect();
-                let safety = reports
-                    .iter()
-                    .map(|report| Safety::assess(report.clone()))
-                    .fold(
-                        0,
-                        |acc, safety| if safety == Safety::Safe { acc + 1 } else { acc },
-                    );
-                safety.to_string()
-            }
-            Solver::Part2 => {
-                let reports: Vec<Report> = input
-                    .lines()
-                    .map(|line| line.split(' ').collect::<Vec<&str>>())
-                    .map(|report| report.iter().map(|level| level.parse().unwrap()).collect())
-                    .collect();
-                let safety = reports
-                    .iter()
-                    .map(|report| Safety::assess_with_tolerance(report.clone(), Some(1)))
-                    .fold(
-                        0,
-                        |acc, safety| if safety == Safety::Safe { acc + 1 } else { acc },
-                    );
-                safety.to_string()
-            }
+    if args.stats {
+        let reports = parse_reports(&contents);
+        println!("{}", Stats::new(&reports, &policy));
+        let shown = args.sparklines.unwrap_or(reports.len());
+        println!("\nSparklines");
+        for (index, report) in reports.iter().take(shown).enumerate() {
+            println!("{:>4}: {}", index + 1, sparkline(report));
         }
     }
+    let result = args.solver.solve(contents, &policy);
+    println!("{}", result);
 }
 
-fn main() {
-    let args = Args::parse();
-    let input = args.path;
-    println!("{:?}", input);
-    let contents = std::fs::read_to_string(input).unwrap();
-    println!("{}", contents);
-    let result = args.solver.solve(contents);
-    println!("{}", result);
+fn exit_with(error: PolicyError) -> ! {
+    eprintln!("Invalid safety policy: {}", error);
+    std::process::exit(1);
 }
@@ -0,0 +1,111 @@
+use std::{
+    io::BufRead,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{parse_levels_into, policy::SafetyPolicy, Report, Safety};
+
+/// Counts gathered while streaming reports through the thread pool
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Throughput {
+    pub reports: usize,
+    pub safe_reports: usize,
+    pub bytes: usize,
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    pub fn reports_per_second(&self) -> f64 {
+        self.reports as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn megabytes_per_second(&self) -> f64 {
+        self.bytes as f64 / 1_000_000.0 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Reads reports in chunks of `chunk_size` lines and assesses them on
+/// `thread_count` threads. Only one chunk per thread is kept in memory at a
+/// time and each thread parses into a single reused buffer.
+pub fn assess_stream(
+    mut reader: impl BufRead,
+    policy: &SafetyPolicy,
+    thread_count: usize,
+    chunk_size: usize,
+) -> std::io::Result<Throughput> {
+    let started = Instant::now();
+    let thread_count = thread_count.max(1);
+    // Empty chunks would end the stream before anything was read
+    let chunk_size = chunk_size.max(1);
+    // Bounded so the reader can't get too far ahead of the workers
+    let (chunk_tx, chunk_rx) = mpsc::sync_channel::<String>(thread_count * 2);
+    let chunk_rx = Arc::new(Mutex::new(chunk_rx));
+    let (tx, rx) = mpsc::channel();
+    let bytes = thread::scope(|scope| {
+        for _ in 0..thread_count {
+            let chunk_rx = Arc::clone(&chunk_rx);
+            let tx = tx.clone();
+            scope.spawn(move || {
+                let mut levels = Report::new();
+                let mut counts = (0, 0);
+                loop {
+                    // Hold the lock only for as long as it takes to get a chunk
+                    let chunk = chunk_rx.lock().unwrap().recv();
+                    let Ok(chunk) = chunk else {
+                        break;
+                    };
+                    for line in chunk.lines().filter(|line| !line.trim().is_empty()) {
+                        parse_levels_into(line, &mut levels);
+                        counts.0 += 1;
+                        if Safety::assess(&levels, policy) == Safety::Safe {
+                            counts.1 += 1;
+                        }
+                    }
+                }
+                tx.send(counts).unwrap();
+            });
+        }
+        drop(tx);
+        let result = send_chunks(&mut reader, &chunk_tx, chunk_size);
+        // Closing the channel lets the workers finish
+        drop(chunk_tx);
+        result
+    })?;
+    let (reports, safe_reports) = rx
+        .iter()
+        .fold((0, 0), |acc, counts| (acc.0 + counts.0, acc.1 + counts.1));
+    Ok(Throughput {
+        reports,
+        safe_reports,
+        bytes,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Splits the input into chunks of whole lines and hands them to the
+/// workers, returns how many bytes were read
+fn send_chunks(
+    reader: &mut impl BufRead,
+    chunk_tx: &mpsc::SyncSender<String>,
+    chunk_size: usize,
+) -> std::io::Result<usize> {
+    let mut bytes = 0;
+    loop {
+        let mut chunk = String::new();
+        let mut lines = 0;
+        while lines < chunk_size {
+            let read = reader.read_line(&mut chunk)?;
+            if read == 0 {
+                break;
+            }
+            bytes += read;
+            lines += 1;
+        }
+        if chunk.is_empty() {
+            return Ok(bytes);
+        }
+        chunk_tx.send(chunk).unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+use crate::{Direction, Level};
+
+/// Which way the levels of a safe report are allowed to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Directions {
+    /// Only strictly increasing reports
+    Increasing,
+    /// Only strictly decreasing reports
+    Decreasing,
+    /// Strictly increasing or strictly decreasing reports
+    Either,
+    /// Increasing or decreasing reports that may have plateaus
+    NonStrict,
+}
+
+impl Directions {
+    pub fn candidates(&self) -> &'static [Direction] {
+        match self {
+            Directions::Increasing => &[Direction::Increasing],
+            Directions::Decreasing => &[Direction::Decreasing],
+            Directions::Either | Directions::NonStrict => {
+                &[Direction::Increasing, Direction::Decreasing]
+            }
+        }
+    }
+
+    pub fn allows_plateaus(&self) -> bool {
+        matches!(self, Directions::NonStrict)
+    }
+}
+
+/// Why a policy can't be used
+#[derive(Debug)]
+pub enum PolicyError {
+    /// The TOML file couldn't be read as a policy
+    Toml(toml::de::Error),
+    /// Steps have to be at least 1, plateaus are what `non-strict` is for
+    MinStepTooSmall(Level),
+    /// No difference could ever be allowed
+    StepsOutOfOrder { min_step: Level, max_step: Level },
+}
+
+impl Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::Toml(error) => write!(f, "{}", error),
+            PolicyError::MinStepTooSmall(min_step) => write!(
+                f,
+                "min-step is {} but has to be at least 1, use non-strict directions to allow plateaus",
+                min_step
+            ),
+            PolicyError::StepsOutOfOrder { min_step, max_step } => write!(
+                f,
+                "min-step {} is larger than max-step {}",
+                min_step, max_step
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl From<toml::de::Error> for PolicyError {
+    fn from(value: toml::de::Error) -> Self {
+        PolicyError::Toml(value)
+    }
+}
+
+/// Checks the steps that are known, either one may be missing
+fn validate_steps(min_step: Option<Level>, max_step: Option<Level>) -> Result<(), PolicyError> {
+    if let Some(min_step) = min_step.filter(|min_step| *min_step < 1) {
+        return Err(PolicyError::MinStepTooSmall(min_step));
+    }
+    if let (Some(min_step), Some(max_step)) = (min_step, max_step) {
+        if min_step > max_step {
+            return Err(PolicyError::StepsOutOfOrder { min_step, max_step });
+        }
+    }
+    Ok(())
+}
+
+/// Rules a report has to follow to be considered safe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyPolicy {
+    pub min_step: Level,
+    pub max_step: Level,
+    pub directions: Directions,
+    /// How many levels the Problem Dampener may remove
+    pub tolerance: usize,
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            directions: Directions::Either,
+            tolerance: 0,
+        }
+    }
+}
+
+impl SafetyPolicy {
+    pub fn with_tolerance(tolerance: usize) -> Self {
+        Self {
+            tolerance,
+            ..Default::default()
+        }
+    }
+
+    /// Makes sure some step is allowed and that only `non-strict` allows
+    /// plateaus
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        validate_steps(Some(self.min_step), Some(self.max_step))
+    }
+
+    /// Checks if going from `previous` to `next` is allowed when the report
+    /// is moving in `direction`
+    pub fn is_safe_step(&self, direction: &Direction, previous: Level, next: Level) -> bool {
+        let delta = direction.step(previous, next);
+        (delta == 0 && self.directions.allows_plateaus())
+            || (self.min_step as i128..=self.max_step as i128).contains(&delta)
+    }
+}
+
+/// Partial policy, read from the command line or a TOML file. Anything left
+/// out keeps the value it already had.
+#[derive(Debug, Default, Clone, clap::Args, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PolicyOverrides {
+    /// Smallest allowed difference between adjacent levels
+    #[arg(long)]
+    pub min_step: Option<Level>,
+    /// Largest allowed difference between adjacent levels
+    #[arg(long)]
+    pub max_step: Option<Level>,
+    /// Which way the levels are allowed to move
+    #[arg(long, value_enum)]
+    pub directions: Option<Directions>,
+    /// How many levels can be removed to make a report safe
+    #[arg(long)]
+    pub tolerance: Option<usize>,
+}
+
+impl PolicyOverrides {
+    /// Reads the overrides from a TOML file, rejecting steps that can't
+    /// make a valid policy
+    pub fn from_toml(contents: &str) -> Result<Self, PolicyError> {
+        let overrides: Self = toml::from_str(contents)?;
+        validate_steps(overrides.min_step, overrides.max_step)?;
+        Ok(overrides)
+    }
+
+    pub fn apply(&self, policy: &mut SafetyPolicy) {
+        if let Some(min_step) = self.min_step {
+            policy.min_step = min_step;
+        }
+        if let Some(max_step) = self.max_step {
+            policy.max_step = max_step;
+        }
+        if let Some(directions) = self.directions {
+            policy.directions = directions;
+        }
+        if let Some(tolerance) = self.tolerance {
+            policy.tolerance = tolerance;
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{
+    assessment::{Assessment, Verdict},
+    policy::SafetyPolicy,
+    render_bars, Direction, Level, Report,
+};
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Overall shape of a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Trend {
+    Moving(Direction),
+    /// Every level is the same, or there is only one
+    Flat,
+    /// Goes up and down
+    Mixed,
+}
+
+impl Trend {
+    pub fn of(report: &[Level]) -> Self {
+        let mut trend = Trend::Flat;
+        for pair in report.windows(2) {
+            let direction = match pair[0].cmp(&pair[1]) {
+                std::cmp::Ordering::Less => Direction::Increasing,
+                std::cmp::Ordering::Equal => continue,
+                std::cmp::Ordering::Greater => Direction::Decreasing,
+            };
+            match trend {
+                Trend::Flat => trend = Trend::Moving(direction),
+                Trend::Moving(current) if current != direction => return Trend::Mixed,
+                _ => {}
+            }
+        }
+        trend
+    }
+}
+
+impl Display for Trend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trend::Moving(Direction::Increasing) => write!(f, "increasing"),
+            Trend::Moving(Direction::Decreasing) => write!(f, "decreasing"),
+            Trend::Flat => write!(f, "flat"),
+            Trend::Mixed => write!(f, "mixed"),
+        }
+    }
+}
+
+/// Distributions over a whole set of reports
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    /// How many times each step between adjacent levels shows up
+    pub deltas: BTreeMap<i128, usize>,
+    pub trends: BTreeMap<Trend, usize>,
+    /// How many reports have each number of levels
+    pub lengths: BTreeMap<usize, usize>,
+    pub safe_reports: usize,
+    pub dampened_reports: usize,
+    pub unsafe_reports: usize,
+}
+
+impl Stats {
+    pub fn new(reports: &[Report], policy: &SafetyPolicy) -> Self {
+        let mut stats = Self::default();
+        for report in reports {
+            for pair in report.windows(2) {
+                *stats
+                    .deltas
+                    .entry(Direction::Increasing.step(pair[0], pair[1]))
+                    .or_insert(0) += 1;
+            }
+            *stats.trends.entry(Trend::of(report)).or_insert(0) += 1;
+            *stats.lengths.entry(report.len()).or_insert(0) += 1;
+            match Assessment::new(report, policy).verdict {
+                Verdict::Safe => stats.safe_reports += 1,
+                Verdict::Dampened => stats.dampened_reports += 1,
+                Verdict::Unsafe => stats.unsafe_reports += 1,
+            }
+        }
+        stats
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let deltas: Vec<(String, usize)> = self
+            .deltas
+            .iter()
+            .map(|(delta, count)| (format!("step {:+}", delta), *count))
+            .collect();
+        let trends: Vec<(String, usize)> = self
+            .trends
+            .iter()
+            .map(|(trend, count)| (trend.to_string(), *count))
+            .collect();
+        let lengths: Vec<(String, usize)> = self
+            .lengths
+            .iter()
+            .map(|(length, count)| (format!("{} levels", length), *count))
+            .collect();
+        let verdicts = vec![
+            ("safe".to_string(), self.safe_reports),
+            ("dampened".to_string(), self.dampened_reports),
+            ("unsafe".to_string(), self.unsafe_reports),
+        ];
+        writeln!(f, "Step deltas\n{}", render_bars(&deltas, 40))?;
+        writeln!(f, "\nTrends\n{}", render_bars(&trends, 40))?;
+        writeln!(f, "\nReport lengths\n{}", render_bars(&lengths, 40))?;
+        write!(f, "\nVerdicts\n{}", render_bars(&verdicts, 40))
+    }
+}
+
+/// Draws the levels of a report as a single line of block characters,
+/// scaled between the lowest and highest level of the report
+pub fn sparkline(report: &[Level]) -> String {
+    let (Some(low), Some(high)) = (report.iter().min(), report.iter().max()) else {
+        return String::new();
+    };
+    let range = (*high as i128 - *low as i128).max(1);
+    report
+        .iter()
+        .map(|level| {
+            SPARKS[((*level as i128 - *low as i128) * (SPARKS.len() as i128 - 1) / range) as usize]
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+use serde::Serialize;
+
+use crate::{
+    instructions::InstructionSet,
+    interpreter::{Interpreter, Outcome},
+    lexer::Lexer,
+    Error, Value,
+};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const APPLIED: &str = "\x1b[1;32m";
+const DISABLED: &str = "\x1b[31m";
+const FAILED: &str = "\x1b[1;35m";
+const TOGGLE: &str = "\x1b[1;4;33m";
+
+/// One instruction found in the input and what it did
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    pub instruction: &'static str,
+    /// Byte offset where the instruction starts
+    pub start: usize,
+    /// Byte offset right after the instruction
+    pub end: usize,
+    pub operands: Vec<Value>,
+    pub outcome: Outcome,
+    /// How much the instruction changed the accumulator, the product for
+    /// `mul`
+    pub value: Value,
+}
+
+/// Runs the input through the interpreter and records every instruction
+pub fn audit(
+    input: &str,
+    instructions: &InstructionSet,
+    conditionals: bool,
+) -> Result<Vec<Entry>, Error> {
+    let mut interpreter = Interpreter::new(instructions, conditionals);
+    Lexer::new(input, instructions)
+        .map(|token| {
+            let token = token?;
+            let before = interpreter.accumulator;
+            let outcome = interpreter.step(&token)?;
+            Ok(Entry {
+                instruction: instructions.get(token.opcode).name,
+                start: token.span.start,
+                end: token.span.end,
+                operands: token.operands,
+                outcome,
+                value: interpreter.accumulator.saturating_sub(before),
+            })
+        })
+        .collect()
+}
+
+/// Colors the input for a terminal: applied instructions in green, disabled
+/// ones in red, `do()` and `don't()` underlined and everything else dimmed
+pub fn highlight(input: &str, entries: &[Entry]) -> String {
+    let mut output = String::with_capacity(input.len() * 2);
+    let mut position = 0;
+    for entry in entries {
+        output.push_str(DIM);
+        output.push_str(&input[position..entry.start]);
+        output.push_str(RESET);
+        let color = match entry.outcome {
+            Outcome::Applied => APPLIED,
+            Outcome::Disabled => DISABLED,
+            Outcome::Failed => FAILED,
+            Outcome::Toggled => TOGGLE,
+        };
+        output.push_str(color);
+        output.push_str(&input[entry.start..entry.end]);
+        output.push_str(RESET);
+        position = entry.end;
+    }
+    output.push_str(DIM);
+    output.push_str(&input[position..]);
+    output.push_str(RESET);
+    output
+}
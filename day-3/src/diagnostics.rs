@@ -0,0 +1,192 @@
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{
+    instructions::{Instruction, InstructionSet},
+    lexer::Lexer,
+};
+
+const OPEN_BRACKETS: &[u8] = b"([{<";
+const CLOSE_BRACKETS: &[u8] = b")]}>";
+/// Near misses longer than this are most likely unrelated garbage
+const MAX_NEAR_MISS_LENGTH: usize = 48;
+
+/// Why a near miss wasn't accepted as an instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    SpaceBeforeParenthesis,
+    WrongBrackets { open: char, close: char },
+    SpaceInOperands,
+    MissingComma,
+    WrongArity { expected: usize, found: usize },
+    TooManyDigits { operand: String, max: usize },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::SpaceBeforeParenthesis => write!(f, "space before the parenthesis"),
+            Problem::WrongBrackets { open, close } => {
+                write!(f, "uses {}{} instead of ()", open, close)
+            }
+            Problem::SpaceInOperands => write!(f, "whitespace around the operands"),
+            Problem::MissingComma => write!(f, "operands not separated by a comma"),
+            Problem::WrongArity { expected, found } => {
+                write!(f, "expected {} operands, found {}", expected, found)
+            }
+            Problem::TooManyDigits { operand, max } => {
+                write!(f, "operand {} has more than {} digits", operand, max)
+            }
+        }
+    }
+}
+
+/// Something that looks like an instruction but was skipped by the lexer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub instruction: &'static str,
+    /// Byte offset where the near miss starts
+    pub start: usize,
+    /// Byte offset right after the near miss
+    pub end: usize,
+    pub text: String,
+    pub problems: Vec<Problem>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}..{} {:?}: {}",
+            self.start,
+            self.end,
+            self.text,
+            self.problems
+                .iter()
+                .map(|problem| problem.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Finds every instruction name that is followed by something close to an
+/// operand list but that the lexer didn't accept
+pub fn diagnose(input: &str, instructions: &InstructionSet) -> Vec<Diagnostic> {
+    let valid: HashSet<usize> = Lexer::new(input, instructions)
+        .filter_map(|token| token.ok())
+        .map(|token| token.span.start)
+        .collect();
+    let bytes = input.as_bytes();
+    let mut diagnostics = Vec::new();
+    for start in 0..bytes.len() {
+        if valid.contains(&start) {
+            continue;
+        }
+        let near_miss = instructions
+            .instructions
+            .iter()
+            .filter(|instruction| bytes[start..].starts_with(instruction.name.as_bytes()))
+            .find_map(|instruction| near_miss_at(bytes, start, instruction, instructions));
+        if let Some((instruction, end, problems)) = near_miss {
+            diagnostics.push(Diagnostic {
+                instruction: instruction.name,
+                start,
+                end,
+                text: input[start..end].to_string(),
+                problems,
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Parses `instruction` at `start` leniently, allowing whitespace, other
+/// brackets, missing commas and long operands, and lists what was wrong
+fn near_miss_at<'a>(
+    bytes: &[u8],
+    start: usize,
+    instruction: &'a Instruction,
+    instructions: &InstructionSet,
+) -> Option<(&'a Instruction, usize, Vec<Problem>)> {
+    let is_space = |byte: &u8| *byte == b' ' || *byte == b'\t';
+    let skip_spaces = |position: usize| {
+        position
+            + bytes[position.min(bytes.len())..]
+                .iter()
+                .take_while(|byte| is_space(byte))
+                .count()
+    };
+    let mut problems = Vec::new();
+    let mut position = start + instruction.name.len();
+    let after_name = skip_spaces(position);
+    if after_name > position {
+        problems.push(Problem::SpaceBeforeParenthesis);
+    }
+    position = after_name;
+    let open = *bytes.get(position)?;
+    if !OPEN_BRACKETS.contains(&open) {
+        return None;
+    }
+    position += 1;
+    let mut operands: Vec<&[u8]> = Vec::new();
+    let mut spaced = false;
+    let mut missing_comma = false;
+    let close = loop {
+        let after_spaces = skip_spaces(position);
+        spaced |= after_spaces > position;
+        position = after_spaces;
+        let byte = *bytes.get(position)?;
+        if CLOSE_BRACKETS.contains(&byte) {
+            break byte;
+        }
+        if !operands.is_empty() {
+            if byte == b',' {
+                let after_spaces = skip_spaces(position + 1);
+                spaced |= after_spaces > position + 1;
+                position = after_spaces;
+            } else {
+                missing_comma = true;
+            }
+        }
+        let digits = bytes[position.min(bytes.len())..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if digits == 0 || position - start > MAX_NEAR_MISS_LENGTH {
+            return None;
+        }
+        operands.push(&bytes[position..position + digits]);
+        position += digits;
+    };
+    let end = position + 1;
+    if open != b'(' || close != b')' {
+        problems.push(Problem::WrongBrackets {
+            open: open as char,
+            close: close as char,
+        });
+    }
+    if spaced {
+        problems.push(Problem::SpaceInOperands);
+    }
+    if missing_comma {
+        problems.push(Problem::MissingComma);
+    }
+    if operands.len() != instruction.arity {
+        problems.push(Problem::WrongArity {
+            expected: instruction.arity,
+            found: operands.len(),
+        });
+    }
+    if let Some(max) = instructions.max_digits {
+        for operand in operands.iter().filter(|operand| operand.len() > max) {
+            problems.push(Problem::TooManyDigits {
+                operand: String::from_utf8_lossy(operand).to_string(),
+                max,
+            });
+        }
+    }
+    if problems.is_empty() {
+        return None;
+    }
+    Some((instruction, end, problems))
+}
@@ -0,0 +1,143 @@
+use crate::Value;
+
+/// Why an instruction couldn't compute a new accumulator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The result doesn't fit in a `Value`, this stops the interpreter
+    Overflow,
+    /// The operation has no result, like a division by zero, and is skipped
+    Undefined,
+}
+
+/// What running an instruction does to the interpreter
+#[derive(Debug, Clone, Copy)]
+pub enum Effect {
+    /// Computes the new accumulator from the current one and the operands
+    Apply(fn(Value, &[Value]) -> Result<Value, Fault>),
+    /// Turns on the instructions that apply to the accumulator
+    Enable,
+    /// Turns off the instructions that apply to the accumulator
+    Disable,
+}
+
+/// An instruction the lexer can find, written as `name(a,b,...)` with
+/// exactly `arity` operands
+#[derive(Debug, Clone, Copy)]
+pub struct Instruction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub effect: Effect,
+}
+
+impl Instruction {
+    pub const fn new(name: &'static str, arity: usize, effect: Effect) -> Self {
+        Self {
+            name,
+            arity,
+            effect,
+        }
+    }
+}
+
+pub const MUL: Instruction = Instruction::new(
+    "mul",
+    2,
+    Effect::Apply(|acc, operands| {
+        operands[0]
+            .checked_mul(operands[1])
+            .and_then(|product| acc.checked_add(product))
+            .ok_or(Fault::Overflow)
+    }),
+);
+pub const DO: Instruction = Instruction::new("do", 0, Effect::Enable);
+pub const DONT: Instruction = Instruction::new("don't", 0, Effect::Disable);
+pub const ADD: Instruction = Instruction::new(
+    "add",
+    2,
+    Effect::Apply(|acc, operands| {
+        operands[0]
+            .checked_add(operands[1])
+            .and_then(|sum| acc.checked_add(sum))
+            .ok_or(Fault::Overflow)
+    }),
+);
+pub const SUB: Instruction = Instruction::new(
+    "sub",
+    2,
+    Effect::Apply(|acc, operands| {
+        operands[0]
+            .checked_sub(operands[1])
+            .and_then(|difference| acc.checked_add(difference))
+            .ok_or(Fault::Overflow)
+    }),
+);
+pub const DIV: Instruction = Instruction::new(
+    "div",
+    2,
+    Effect::Apply(|acc, operands| {
+        if operands[1] == 0 {
+            return Err(Fault::Undefined);
+        }
+        operands[0]
+            .checked_div(operands[1])
+            .and_then(|quotient| acc.checked_add(quotient))
+            .ok_or(Fault::Overflow)
+    }),
+);
+
+/// Operands longer than this are rejected, like the puzzle says
+pub const PUZZLE_MAX_DIGITS: usize = 3;
+
+/// Table of every instruction the lexer looks for. The index of an
+/// instruction in the table is its opcode.
+#[derive(Debug, Clone)]
+pub struct InstructionSet {
+    pub instructions: Vec<Instruction>,
+    /// Most digits an operand can have, `None` accepts any length
+    pub max_digits: Option<usize>,
+}
+
+impl InstructionSet {
+    /// `mul`, `do` and `don't`, the instructions from the puzzle
+    pub fn puzzle() -> Self {
+        Self {
+            instructions: vec![MUL, DO, DONT],
+            max_digits: Some(PUZZLE_MAX_DIGITS),
+        }
+    }
+
+    /// The puzzle instructions plus `add`, `sub` and `div`
+    pub fn extended() -> Self {
+        Self::puzzle().with(ADD).with(SUB).with(DIV)
+    }
+
+    pub fn with(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn with_max_digits(mut self, max_digits: Option<usize>) -> Self {
+        self.max_digits = max_digits;
+        self
+    }
+
+    pub fn get(&self, opcode: usize) -> &Instruction {
+        &self.instructions[opcode]
+    }
+}
+
+/// Instruction sets that can be picked from the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Dialect {
+    Puzzle,
+    Extended,
+}
+
+impl From<Dialect> for InstructionSet {
+    fn from(value: Dialect) -> Self {
+        match value {
+            Dialect::Puzzle => InstructionSet::puzzle(),
+            Dialect::Extended => InstructionSet::extended(),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use serde::Serialize;
+
+use crate::{
+    instructions::{Effect, Fault, InstructionSet},
+    lexer::Token,
+    Error, Value,
+};
+
+/// What happened when the interpreter ran a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// Changed the accumulator
+    Applied,
+    /// Would have changed the accumulator but instructions are disabled
+    Disabled,
+    /// The instruction couldn't run with those operands
+    Failed,
+    /// Turned instructions on or off
+    Toggled,
+}
+
+/// Runs tokens from the lexer against an accumulator, keeping track of
+/// whether instructions are enabled
+#[derive(Debug, Clone)]
+pub struct Interpreter<'a> {
+    instructions: &'a InstructionSet,
+    /// When false `do()` and `don't()` are ignored, like in Part1
+    pub conditionals: bool,
+    pub enabled: bool,
+    pub accumulator: Value,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(instructions: &'a InstructionSet, conditionals: bool) -> Self {
+        Self {
+            instructions,
+            conditionals,
+            enabled: true,
+            accumulator: 0,
+        }
+    }
+
+    /// Applies a single token, fails if the accumulator would overflow
+    pub fn step(&mut self, token: &Token) -> Result<Outcome, Error> {
+        let instruction = self.instructions.get(token.opcode);
+        let outcome = match instruction.effect {
+            Effect::Apply(apply) => {
+                if self.conditionals && !self.enabled {
+                    return Ok(Outcome::Disabled);
+                }
+                match apply(self.accumulator, &token.operands) {
+                    Ok(accumulator) => {
+                        self.accumulator = accumulator;
+                        Outcome::Applied
+                    }
+                    Err(Fault::Undefined) => Outcome::Failed,
+                    Err(Fault::Overflow) => {
+                        return Err(Error::Overflow {
+                            instruction: instruction.name,
+                            span: token.span.clone(),
+                        })
+                    }
+                }
+            }
+            Effect::Enable => {
+                self.enabled = true;
+                Outcome::Toggled
+            }
+            Effect::Disable => {
+                self.enabled = false;
+                Outcome::Toggled
+            }
+        };
+        Ok(outcome)
+    }
+
+    pub fn run(
+        &mut self,
+        tokens: impl IntoIterator<Item = Result<Token, Error>>,
+    ) -> Result<Value, Error> {
+        for token in tokens {
+            self.step(&token?)?;
+        }
+        Ok(self.accumulator)
+    }
+}
@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+use crate::{instructions::InstructionSet, Error, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// Index of the instruction in the instruction set
+    pub opcode: usize,
+    pub operands: Vec<Value>,
+    /// Byte range of the instruction in the input
+    pub span: Range<usize>,
+}
+
+/// Why the lexer couldn't read an instruction at some position
+enum Stop {
+    /// What's there is not an instruction
+    NoMatch,
+    /// The input ends in the middle of what could be an instruction
+    NeedMore,
+}
+
+/// Single pass lexer over corrupted memory. Anything that isn't a valid
+/// instruction from the set is skipped one byte at a time, so
+/// `mul(mul(2,3)` still finds the inner `mul(2,3)`.
+pub struct Lexer<'a> {
+    input: &'a [u8],
+    instructions: &'a InstructionSet,
+    position: usize,
+    /// Added to every span, for chunks that don't start at the beginning
+    offset: usize,
+    /// False when more input may follow, then the lexer stops before an
+    /// instruction that runs past the end instead of skipping it
+    last: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str, instructions: &'a InstructionSet) -> Self {
+        Self::chunk(input.as_bytes(), instructions, 0, true)
+    }
+
+    /// Lexer over one chunk of a larger input that starts `offset` bytes
+    /// in. Unless it's the `last` chunk, check `position` once it's done to
+    /// know which bytes have to be carried over to the next one.
+    pub fn chunk(
+        input: &'a [u8],
+        instructions: &'a InstructionSet,
+        offset: usize,
+        last: bool,
+    ) -> Self {
+        Self {
+            input,
+            instructions,
+            position: 0,
+            offset,
+            last,
+        }
+    }
+
+    /// Where the lexer is in the chunk, everything before it has been read
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn byte_at(&self, position: usize) -> Result<u8, Stop> {
+        match self.input.get(position) {
+            Some(byte) => Ok(*byte),
+            None if self.last => Err(Stop::NoMatch),
+            None => Err(Stop::NeedMore),
+        }
+    }
+
+    fn expect(&self, position: usize, expected: &[u8]) -> Result<usize, Stop> {
+        for (index, expected) in expected.iter().enumerate() {
+            if self.byte_at(position + index)? != *expected {
+                return Err(Stop::NoMatch);
+            }
+        }
+        Ok(position + expected.len())
+    }
+
+    /// Tries to read an instruction at `start`, returns it with the digits
+    /// of its operands and where it ends. Instructions earlier in the set
+    /// win, so if one of them needs more input we have to wait for it.
+    fn instruction_at(&self, start: usize) -> Result<(usize, Vec<&'a [u8]>, usize), Stop> {
+        for (opcode, instruction) in self.instructions.instructions.iter().enumerate() {
+            let found = self
+                .expect(start, instruction.name.as_bytes())
+                .and_then(|position| self.expect(position, b"("))
+                .and_then(|position| self.operands_at(position, instruction.arity));
+            match found {
+                Ok((operands, end)) => return Ok((opcode, operands, end)),
+                Err(Stop::NeedMore) => return Err(Stop::NeedMore),
+                Err(Stop::NoMatch) => continue,
+            }
+        }
+        Err(Stop::NoMatch)
+    }
+
+    /// Reads `arity` comma separated numbers and the closing parenthesis
+    fn operands_at(&self, start: usize, arity: usize) -> Result<(Vec<&'a [u8]>, usize), Stop> {
+        let mut operands = Vec::with_capacity(arity);
+        let mut position = start;
+        for index in 0..arity {
+            if index > 0 {
+                position = self.expect(position, b",")?;
+            }
+            let (operand, end) = self.number_at(position)?;
+            operands.push(operand);
+            position = end;
+        }
+        let end = self.expect(position, b")")?;
+        Ok((operands, end))
+    }
+
+    /// Reads one or more digits at `start`, returns them and the position
+    /// right after the last digit
+    fn number_at(&self, start: usize) -> Result<(&'a [u8], usize), Stop> {
+        let digits = self.input[start.min(self.input.len())..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if self.instructions.max_digits.is_some_and(|max| digits > max) {
+            return Err(Stop::NoMatch);
+        }
+        let end = start + digits;
+        // Digits that reach the end of the chunk may go on in the next one
+        self.byte_at(end)?;
+        if digits == 0 {
+            return Err(Stop::NoMatch);
+        }
+        Ok((&self.input[start..end], end))
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.input.len() {
+            let start = self.position;
+            let (opcode, digits, end) = match self.instruction_at(start) {
+                Ok(found) => found,
+                Err(Stop::NeedMore) => return None,
+                Err(Stop::NoMatch) => {
+                    self.position += 1;
+                    continue;
+                }
+            };
+            self.position = end;
+            let span = self.offset + start..self.offset + end;
+            let operands = digits
+                .iter()
+                .map(|digits| {
+                    // Only digits get here, so the only way to fail is being
+                    // too large
+                    let digits = std::str::from_utf8(digits).unwrap();
+                    digits
+                        .parse::<Value>()
+                        .map_err(|_| Error::OperandOutOfRange {
+                            operand: digits.to_string(),
+                            span: span.clone(),
+                        })
+                })
+                .collect::<Result<Vec<Value>, Error>>();
+            return Some(operands.map(|operands| Token {
+                opcode,
+                operands,
+                span,
+            }));
+        }
+        None
+    }
+}
@@ -0,0 +1,341 @@
+use std::{fmt::Display, ops::Range};
+
+pub mod audit;
+pub mod diagnostics;
+pub mod instructions;
+pub mod interpreter;
+pub mod lexer;
+pub mod stream;
+
+/// Operands and the accumulator are kept as 128 bit integers and every
+/// operation is checked, so inputs that would overflow an `i64` still give
+/// the right answer
+pub type Value = i128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// An operand has too many digits to fit in a `Value`
+    OperandOutOfRange { operand: String, span: Range<usize> },
+    /// Running an instruction overflowed the accumulator
+    Overflow {
+        instruction: &'static str,
+        span: Range<usize>,
+    },
+    /// Reading the input failed
+    Io(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::OperandOutOfRange { operand, span } => write!(
+                f,
+                "Operand {} at {}..{} is out of range, the largest supported is {}",
+                operand,
+                span.start,
+                span.end,
+                Value::MAX
+            ),
+            Error::Overflow { instruction, span } => write!(
+                f,
+                "Running {} at {}..{} overflowed the accumulator",
+                instruction, span.start, span.end
+            ),
+            Error::Io(error) => write!(f, "Could not read the input: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use audit::{audit, highlight};
+    use diagnostics::{diagnose, Problem};
+    use instructions::InstructionSet;
+    use instructions::{Effect, Fault, Instruction};
+    use interpreter::Interpreter;
+    use interpreter::Outcome;
+    use lexer::Lexer;
+    use stream::run_stream;
+
+    fn run(input: &str, instructions: &InstructionSet, conditionals: bool) -> Result<Value, Error> {
+        Interpreter::new(instructions, conditionals).run(Lexer::new(input, instructions))
+    }
+
+    /// Xorshift, so generated inputs are the same on every run
+    fn numbers(seed: u64) -> impl FnMut() -> u64 {
+        let mut state = seed;
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        }
+    }
+
+    const PART1: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+    const PART2: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn examples() {
+        let instructions = InstructionSet::puzzle();
+        assert_eq!(run(PART1, &instructions, false), Ok(161));
+        assert_eq!(run(PART2, &instructions, true), Ok(48));
+        assert_eq!(run("mul(mul(2,3)", &instructions, false), Ok(6));
+    }
+
+    /// What the old `mul\((\d+),(\d+)\)` regex found. A match can't contain
+    /// another `mul(`, so every match starts right after one.
+    fn regex_sum(input: &str) -> Value {
+        input
+            .split("mul(")
+            .skip(1)
+            .filter_map(|rest| {
+                let (left, rest) = rest.split_once(',')?;
+                let (right, _) = rest.split_once(')')?;
+                let is_number =
+                    |digits: &str| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+                if !is_number(left) || !is_number(right) {
+                    return None;
+                }
+                Some(left.parse::<Value>().unwrap() * right.parse::<Value>().unwrap())
+            })
+            .sum()
+    }
+
+    #[test]
+    fn matches_the_regex() {
+        let mut next = numbers(0x2024_1203);
+        let fragments = ["mul(", "mul", "(", ")", ",", "mu", "l", "x", " ", "]"];
+        for _ in 0..200 {
+            let input: String = (0..40)
+                .map(|_| {
+                    if next().is_multiple_of(3) {
+                        (next() % 10_000).to_string()
+                    } else {
+                        fragments[(next() % fragments.len() as u64) as usize].to_string()
+                    }
+                })
+                .collect();
+            let instructions = InstructionSet::puzzle().with_max_digits(None);
+            assert_eq!(
+                run(&input, &instructions, false),
+                Ok(regex_sum(&input)),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn extended_instructions() {
+        let instructions = InstructionSet::extended();
+        let input = "add(2,3)sub(10,4)div(9,2)mul(2,2)sub(1,9)";
+        assert_eq!(run(input, &instructions, false), Ok(5 + 6 + 4 + 4 - 8));
+        // The puzzle set doesn't know them
+        assert_eq!(run(input, &InstructionSet::puzzle(), false), Ok(4));
+    }
+
+    #[test]
+    fn division_by_zero_is_skipped() {
+        let instructions = InstructionSet::extended();
+        let mut interpreter = Interpreter::new(&instructions, false);
+        let outcomes: Vec<Outcome> = Lexer::new("add(1,2)div(4,0)div(8,2)", &instructions)
+            .map(|token| interpreter.step(&token.unwrap()).unwrap())
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![Outcome::Applied, Outcome::Failed, Outcome::Applied]
+        );
+        assert_eq!(interpreter.accumulator, 3 + 4);
+    }
+
+    #[test]
+    fn custom_instructions() {
+        const SQUARE: Instruction = Instruction::new(
+            "sq",
+            1,
+            Effect::Apply(|acc, operands| {
+                operands[0]
+                    .checked_mul(operands[0])
+                    .and_then(|square| acc.checked_add(square))
+                    .ok_or(Fault::Overflow)
+            }),
+        );
+        const CLEAR: Instruction = Instruction::new("clear", 0, Effect::Apply(|_, _| Ok(0)));
+        let instructions = InstructionSet::puzzle().with(SQUARE).with(CLEAR);
+        assert_eq!(
+            run("sq(3)mul(2,2)sq(4,4)sq()", &instructions, false),
+            Ok(13)
+        );
+        assert_eq!(run("mul(2,3)clear()mul(1,1)", &instructions, false), Ok(1));
+        // Disabled like any other instruction that changes the accumulator
+        assert_eq!(run("don't()sq(3)do()sq(2)", &instructions, true), Ok(4));
+    }
+
+    #[test]
+    fn audits_the_example() {
+        let instructions = InstructionSet::puzzle();
+        let entries = audit(PART2, &instructions, true).unwrap();
+        let summary: Vec<(&str, usize, usize, Outcome, Value)> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.instruction,
+                    entry.start,
+                    entry.end,
+                    entry.outcome,
+                    entry.value,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("mul", 1, 9, Outcome::Applied, 8),
+                ("don't", 20, 27, Outcome::Toggled, 0),
+                ("mul", 28, 36, Outcome::Disabled, 0),
+                ("mul", 48, 57, Outcome::Disabled, 0),
+                ("do", 59, 63, Outcome::Toggled, 0),
+                ("mul", 64, 72, Outcome::Applied, 40),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&entries[0]).unwrap(),
+            r#"{"instruction":"mul","start":1,"end":9,"operands":[2,4],"outcome":"applied","value":8}"#
+        );
+
+        let highlighted = highlight(PART2, &entries);
+        assert!(highlighted.starts_with("\x1b[2mx\x1b[0m\x1b[1;32mmul(2,4)\x1b[0m"));
+        assert!(highlighted.contains("\x1b[1;4;33mdon't()\x1b[0m"));
+        assert!(highlighted.contains("\x1b[31mmul(5,5)\x1b[0m"));
+        assert!(highlighted.contains("\x1b[1;4;33mdo()\x1b[0m"));
+        assert!(highlighted.contains("\x1b[1;32mmul(8,5)\x1b[0m"));
+        // Without the colors it's the input again
+        let mut plain = highlighted.clone();
+        for code in [
+            "\x1b[0m",
+            "\x1b[2m",
+            "\x1b[1;32m",
+            "\x1b[31m",
+            "\x1b[1;4;33m",
+        ] {
+            plain = plain.replace(code, "");
+        }
+        assert_eq!(plain, PART2);
+    }
+
+    #[test]
+    fn near_misses() {
+        let input = "mul(1, 2)mul[3,4]mul(12345,6)mul ( 2,3)mul(4,5)";
+        let problems: Vec<(usize, Vec<Problem>)> = diagnose(input, &InstructionSet::puzzle())
+            .into_iter()
+            .map(|diagnostic| (diagnostic.start, diagnostic.problems))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (0, vec![Problem::SpaceInOperands]),
+                (
+                    9,
+                    vec![Problem::WrongBrackets {
+                        open: '[',
+                        close: ']'
+                    }]
+                ),
+                (
+                    17,
+                    vec![Problem::TooManyDigits {
+                        operand: "12345".to_string(),
+                        max: 3
+                    }]
+                ),
+                (
+                    29,
+                    vec![Problem::SpaceBeforeParenthesis, Problem::SpaceInOperands]
+                ),
+            ]
+        );
+        // Without the digit limit the long operand is a real instruction
+        let unlimited = InstructionSet::puzzle().with_max_digits(None);
+        assert_eq!(diagnose(input, &unlimited).len(), 3);
+        assert_eq!(run(input, &unlimited, false), Ok(12345 * 6 + 20));
+    }
+
+    #[test]
+    fn out_of_range_and_overflow() {
+        let instructions = InstructionSet::puzzle().with_max_digits(None);
+        let big = "9".repeat(20);
+        let input = format!("mul(2,3)mul({},{})", big, big);
+        let expected = Err(Error::Overflow {
+            instruction: "mul",
+            span: 8..54,
+        });
+        assert_eq!(run(&input, &instructions, false), expected);
+        let huge = "9".repeat(40);
+        let input = format!("xmul(1,{})", huge);
+        let expected = Err(Error::OperandOutOfRange {
+            operand: huge,
+            span: 1..48,
+        });
+        assert_eq!(run(&input, &instructions, false), expected);
+        // Spans are the same when the input comes in chunks
+        for chunk_size in [1, 3, 64] {
+            let mut interpreter = Interpreter::new(&instructions, false);
+            let streamed = run_stream(
+                input.as_bytes(),
+                &instructions,
+                &mut interpreter,
+                chunk_size,
+            );
+            assert_eq!(streamed, expected);
+        }
+        // Larger than an i64 but still fine
+        let input = format!("mul({},{})", "9".repeat(18), "9".repeat(18));
+        assert!(run(&input, &instructions, false).unwrap() > i64::MAX as Value);
+    }
+
+    #[test]
+    fn stream_matches_whole_input() {
+        let mut next = numbers(0x2024_1237);
+        let fragments = [
+            "mul(", "add(", "sub(", "div(", "do()", "don't()", "do", "don't", "(", ")", ",", "mu",
+            "l", "x", " ", "]",
+        ];
+        let input: String = (0..4000)
+            .map(|_| {
+                if next().is_multiple_of(3) {
+                    // Numbers of every length, including too many digits
+                    (next() % 10u64.pow((next() % 5) as u32 + 1)).to_string()
+                } else {
+                    fragments[(next() % fragments.len() as u64) as usize].to_string()
+                }
+            })
+            .collect();
+        let sets = [
+            InstructionSet::puzzle(),
+            InstructionSet::extended(),
+            InstructionSet::extended().with_max_digits(None),
+        ];
+        for instructions in sets.iter() {
+            for conditionals in [false, true] {
+                let expected = run(&input, instructions, conditionals);
+                assert!(expected.is_ok());
+                for chunk_size in 1..=16 {
+                    let mut interpreter = Interpreter::new(instructions, conditionals);
+                    let streamed =
+                        run_stream(input.as_bytes(), instructions, &mut interpreter, chunk_size);
+                    assert_eq!(streamed, expected, "chunks of {} bytes", chunk_size);
+                }
+            }
+        }
+    }
+}
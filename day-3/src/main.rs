@@ -1,14 +1,61 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::PathBuf,
+};
+
 use clap::Parser;
 
+use day_3::{
+    audit::{audit, highlight},
+    diagnostics::diagnose,
+    instructions::{Dialect, InstructionSet, PUZZLE_MAX_DIGITS},
+    interpreter::Interpreter,
+    stream::run_stream,
+    Error,
+};
+
+/// Files larger than this are not printed
+const MAX_PRINTED_BYTES: u64 = 64 * 1024;
+
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct Args {
-    /// Path of the file to get
-    #[arg(short, long)]
-    path: std::path::PathBuf,
+    /// Paths of the files to get, can be given more than once
+    #[arg(short, long, num_args = 1.., required = true)]
+    path: Vec<PathBuf>,
     /// Solver
     #[arg(short, long, value_enum)]
     solver: Solver,
+    /// Instructions to look for in the corrupted memory
+    #[arg(short, long, value_enum, default_value_t = Dialect::Puzzle)]
+    instructions: Dialect,
+    /// Print the input with the instructions that were found highlighted
+    #[arg(long)]
+    highlight: bool,
+    /// Print every instruction found as JSON
+    #[arg(long)]
+    json: bool,
+    /// Most digits an operand can have, 0 accepts any length
+    #[arg(long, default_value_t = PUZZLE_MAX_DIGITS)]
+    max_digits: usize,
+    /// Print things that look like instructions but were skipped, and why
+    #[arg(long)]
+    diagnose: bool,
+    /// How to run more than one file
+    #[arg(long, value_enum, default_value_t = Programs::Continuous)]
+    programs: Programs,
+    /// Bytes read at a time
+    #[arg(long, default_value_t = 64 * 1024)]
+    chunk_size: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Programs {
+    /// The files are one program, `do()` and `don't()` carry over
+    Continuous,
+    /// Each file is its own program with its own result
+    Independent,
 }
 
 #[derive(Debug, Clone, clap::ValueEnum)]
@@ -18,45 +65,43 @@ enum Solver {
 }
 
 impl Solver {
-    pub fn solve(&self, input: String) -> String {
+    fn conditionals(&self) -> bool {
         match self {
-            Solver::Part1 => {
-                let mut result = 0;
-                let mul_regex = regex::Regex::new(r"mul\((\d+),(\d+)\)").unwrap();
-                for (_, [left, right]) in mul_regex.captures_iter(&input).map(|c| c.extract()) {
-                    println!("mul({},{})", left, right);
-                    let (left, right): (i64, i64) = (left.parse().unwrap(), right.parse().unwrap());
-                    result += left * right;
+            Solver::Part1 => false,
+            Solver::Part2 => true,
+        }
+    }
+
+    pub fn solve(
+        &self,
+        paths: &[PathBuf],
+        programs: Programs,
+        instructions: &InstructionSet,
+        chunk_size: usize,
+    ) -> Result<String, Error> {
+        match programs {
+            Programs::Continuous => {
+                let mut reader: Box<dyn Read> = Box::new(io::empty());
+                for path in paths {
+                    reader = Box::new(reader.chain(File::open(path)?));
                 }
-                result.to_string()
+                let mut interpreter = Interpreter::new(instructions, self.conditionals());
+                let result = run_stream(reader, instructions, &mut interpreter, chunk_size)?;
+                Ok(result.to_string())
             }
-            Solver::Part2 => {
-                let mut result = 0;
-                // Remove anything between don't() and do()
-                let input = format!(
-                    "{}{}{}",
-                    "do()",
-                    input.lines().collect::<Vec<&str>>().join("/* line */"),
-                    "don't()",
-                );
-                let input = input.replace("do()", "\n/* replaced a do */");
-                let input = input
-                    .lines()
-                    .map(|line| {
-                        if let Some(index) = line.find("don't()") {
-                            format!("{}{}\n", &line[..index], "/* clipped the rest */")
-                        } else {
-                            line.to_string()
-                        }
-                    })
-                    .collect::<String>();
-                let mul_regex = regex::Regex::new(r"mul\((\d+),(\d+)\)").unwrap();
-                for (_, [left, right]) in mul_regex.captures_iter(&input).map(|c| c.extract()) {
-                    // println!("mul({},{})", left, right);
-                    let (left, right): (i64, i64) = (left.parse().unwrap(), right.parse().unwrap());
-                    result += left * right;
+            Programs::Independent => {
+                let mut results = Vec::new();
+                for path in paths {
+                    let mut interpreter = Interpreter::new(instructions, self.conditionals());
+                    let result = run_stream(
+                        File::open(path)?,
+                        instructions,
+                        &mut interpreter,
+                        chunk_size,
+                    )?;
+                    results.push(format!("{}: {}", path.display(), result));
                 }
-                result.to_string()
+                Ok(results.join("\n"))
             }
         }
     }
@@ -64,10 +109,66 @@ impl Solver {
 
 fn main() {
     let args = Args::parse();
-    let input = args.path;
-    println!("{:?}", input);
-    let contents = std::fs::read_to_string(input).unwrap();
-    println!("{}", contents);
-    let result = args.solver.solve(contents);
+    let paths = &args.path;
+    println!("{:?}", paths);
+    let max_digits = Some(args.max_digits).filter(|max_digits| *max_digits > 0);
+    let instructions = InstructionSet::from(args.instructions).with_max_digits(max_digits);
+    if args.diagnose || args.highlight || args.json {
+        // Continuous files are audited as one program, so `do()` and
+        // `don't()` carry over and offsets count from the start of the first
+        let programs: Vec<&[PathBuf]> = match args.programs {
+            Programs::Continuous => vec![&paths[..]],
+            Programs::Independent => paths.chunks(1).collect(),
+        };
+        for program in programs {
+            let contents: String = program
+                .iter()
+                .map(|path| std::fs::read_to_string(path).unwrap())
+                .collect();
+            audit_program(&contents, &args, &instructions);
+        }
+    } else {
+        for path in paths.iter() {
+            let size = std::fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0);
+            if size > MAX_PRINTED_BYTES {
+                println!("Ommitting long contents");
+                continue;
+            }
+            let contents = std::fs::read_to_string(path).unwrap();
+            println!("{}", contents);
+        }
+    }
+    let result = args
+        .solver
+        .solve(paths, args.programs, &instructions, args.chunk_size)
+        .unwrap_or_else(|error| exit_with(error));
     println!("{}", result);
 }
+
+/// Prints the diagnostics, highlighted input and JSON asked for
+fn audit_program(contents: &str, args: &Args, instructions: &InstructionSet) {
+    if args.diagnose {
+        for diagnostic in diagnose(contents, instructions) {
+            println!("{}", diagnostic);
+        }
+    }
+    if args.highlight || args.json {
+        let entries = audit(contents, instructions, args.solver.conditionals())
+            .unwrap_or_else(|error| exit_with(error));
+        if args.highlight {
+            println!("{}", highlight(contents, &entries));
+        }
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+        }
+    } else {
+        println!("{}", contents);
+    }
+}
+
+fn exit_with(error: Error) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
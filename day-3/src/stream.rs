@@ -0,0 +1,37 @@
+use std::io::{ErrorKind, Read};
+
+use crate::{instructions::InstructionSet, interpreter::Interpreter, lexer::Lexer, Error, Value};
+
+/// Runs a program read in chunks of `chunk_size` bytes. Only the bytes of an
+/// instruction cut by the end of a chunk are carried over, so memory use
+/// stays constant no matter how large the input is.
+pub fn run_stream(
+    mut reader: impl Read,
+    instructions: &InstructionSet,
+    interpreter: &mut Interpreter,
+    chunk_size: usize,
+) -> Result<Value, Error> {
+    let mut chunk = vec![0; chunk_size.max(1)];
+    let mut buffer: Vec<u8> = Vec::with_capacity(chunk.len() * 2);
+    // Offset of the first byte in the buffer from the start of the input
+    let mut offset = 0;
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(read) => read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        };
+        let last = read == 0;
+        buffer.extend_from_slice(&chunk[..read]);
+        let mut lexer = Lexer::chunk(&buffer, instructions, offset, last);
+        for token in lexer.by_ref() {
+            interpreter.step(&token?)?;
+        }
+        if last {
+            return Ok(interpreter.accumulator);
+        }
+        let consumed = lexer.position();
+        buffer.drain(..consumed);
+        offset += consumed;
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, thread};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{effective_directions, Vec2};
+
+const BITS: usize = u64::BITS as usize;
+
+/// One bit per cell for each letter we care about, rows padded to whole
+/// `u64`s. Checking a letter for 64 cells at once is a single AND, which is
+/// what makes searching huge grids fast.
+#[derive(Debug, Clone)]
+pub struct Bitboard {
+    pub width: usize,
+    pub height: usize,
+    /// `u64`s in each row
+    stride: usize,
+    letters: HashMap<String, Vec<u64>>,
+}
+
+impl Bitboard {
+    /// Reads the grid straight from the input, without making tiles, and
+    /// only keeps the letters that show up in `words`
+    pub fn new(input: &str, words: &[&str]) -> Self {
+        let lines: Vec<&str> = input.lines().filter(|line| !line.is_empty()).collect();
+        let width = lines.first().map_or(0, |line| line.graphemes(true).count());
+        let height = lines.len();
+        let stride = width.div_ceil(BITS);
+        let mut letters: HashMap<String, Vec<u64>> = words
+            .iter()
+            .flat_map(|word| word.graphemes(true))
+            .map(|letter| (letter.to_string(), vec![0; stride * height]))
+            .collect();
+        // Single byte letters can be checked byte by byte, which is a lot
+        // faster than splitting ASCII rows into graphemes
+        let ascii: Vec<(u8, String)> = letters
+            .keys()
+            .filter(|letter| letter.len() == 1)
+            .map(|letter| (letter.as_bytes()[0], letter.clone()))
+            .collect();
+        for (y, line) in lines.iter().enumerate() {
+            let row = y * stride;
+            if line.is_ascii() {
+                for (byte, letter) in &ascii {
+                    let board = letters.get_mut(letter).unwrap();
+                    for (x, _) in line
+                        .bytes()
+                        .take(width)
+                        .enumerate()
+                        .filter(|(_, b)| b == byte)
+                    {
+                        board[row + x / BITS] |= 1 << (x % BITS);
+                    }
+                }
+                continue;
+            }
+            for (x, letter) in line.graphemes(true).take(width).enumerate() {
+                if let Some(board) = letters.get_mut(letter) {
+                    board[row + x / BITS] |= 1 << (x % BITS);
+                }
+            }
+        }
+        Self {
+            width,
+            height,
+            stride,
+            letters,
+        }
+    }
+
+    fn row(&self, letter: &str, y: usize) -> Option<&[u64]> {
+        let board = self.letters.get(letter)?;
+        Some(&board[y * self.stride..(y + 1) * self.stride])
+    }
+
+    /// Counts the occurrences of `word` going in any of `directions`, the
+    /// same as `Dictionary::search` would find on a grid that doesn't wrap.
+    /// Rows are split between `threads`.
+    pub fn count(&self, word: &str, directions: &[Vec2], threads: usize) -> usize {
+        let letters: Vec<&str> = word.graphemes(true).collect();
+        if letters.is_empty() || self.height == 0 {
+            return 0;
+        }
+        let directions = effective_directions(letters.len(), directions);
+        let rows_per_thread = self.height.div_ceil(threads.max(1));
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.height)
+                .step_by(rows_per_thread)
+                .map(|first| {
+                    let rows = first..(first + rows_per_thread).min(self.height);
+                    let letters = &letters;
+                    scope.spawn(move || {
+                        let mut count = 0;
+                        let mut matches = vec![0; self.stride];
+                        for y in rows {
+                            for direction in directions {
+                                self.matches_from_row(y, letters, *direction, &mut matches);
+                                count += matches
+                                    .iter()
+                                    .map(|bits| bits.count_ones() as usize)
+                                    .sum::<usize>();
+                            }
+                        }
+                        count
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .sum()
+        })
+    }
+
+    /// Sets the bit of every cell in row `y` where `letters` start going in
+    /// `direction`
+    fn matches_from_row(&self, y: usize, letters: &[&str], direction: Vec2, matches: &mut [u64]) {
+        matches.fill(0);
+        let Some(first) = self.row(letters[0], y) else {
+            return;
+        };
+        matches.copy_from_slice(first);
+        for (offset, letter) in letters.iter().enumerate().skip(1) {
+            let offset = offset as i64;
+            let row_y = y as i64 + (offset * direction.y);
+            if row_y < 0 || row_y >= self.height as i64 {
+                matches.fill(0);
+                return;
+            }
+            let Some(row) = self.row(letter, row_y as usize) else {
+                matches.fill(0);
+                return;
+            };
+            let shift = offset * direction.x;
+            for (index, bits) in matches.iter_mut().enumerate() {
+                *bits &= shifted(row, index, shift);
+            }
+        }
+    }
+}
+
+/// The `index`th `u64` of `row` moved so bit x holds what was at x + `shift`.
+/// Bits past the ends of the row read as 0.
+fn shifted(row: &[u64], index: usize, shift: i64) -> u64 {
+    let word = |index: i64| -> u64 {
+        if index < 0 || index >= row.len() as i64 {
+            0
+        } else {
+            row[index as usize]
+        }
+    };
+    let words = shift.div_euclid(BITS as i64);
+    let bits = shift.rem_euclid(BITS as i64) as u32;
+    let low = word(index as i64 + words);
+    if bits == 0 {
+        return low;
+    }
+    let high = word(index as i64 + words + 1);
+    (low >> bits) | (high << (BITS as u32 - bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dictionary::Dictionary, generator::Rng, Directions, TileMap};
+
+    #[test]
+    fn counts_like_the_search() {
+        let mut rng = Rng::new(45);
+        let words = ["XMAS", "SAMX", "MAM", "X", "SS"];
+        let directions = [
+            Directions::All,
+            Directions::Forwards,
+            Directions::Knight,
+            // Steps that cross into the next u64, or skip a whole one
+            Directions::Steps(vec![Vec2::new(3, 1), Vec2::new(-5, -2), Vec2::new(65, 0)]),
+        ];
+        let letters = ["X", "M", "A", "S"];
+        for width in [1, 7, 63, 64, 65, 100, 130, 200] {
+            let height = rng.below(12) + 1;
+            let input = (0..height)
+                .map(|_| {
+                    (0..width)
+                        .map(|_| letters[rng.below(letters.len())])
+                        .collect::<String>()
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            let bitboard = Bitboard::new(&input, &words);
+            let map = TileMap::from(input);
+            let dictionary = Dictionary::new(words);
+            for directions in &directions {
+                let directions = directions.vectors();
+                let expected = dictionary.search(&map, &directions);
+                for (word, expected) in words.iter().zip(&expected) {
+                    for threads in [1, 3] {
+                        assert_eq!(
+                            bitboard.count(word, &directions, threads),
+                            expected.len(),
+                            "{} in {}x{} going {:?}",
+                            word,
+                            width,
+                            height,
+                            directions
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
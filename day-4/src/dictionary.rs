@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{effective_directions, Line, Space};
+
+#[derive(Debug, Default, Clone)]
+struct Node {
+    /// Next node for each letter, letters are graphemes like in `Tile`
+    children: HashMap<String, usize>,
+    /// Index of the word that ends on this node
+    word: Option<usize>,
+}
+
+/// Trie of every word to look for, so all of them can be found by walking
+/// each ray of the grid once
+#[derive(Debug, Clone)]
+pub struct Dictionary {
+    pub words: Vec<String>,
+    nodes: Vec<Node>,
+}
+
+impl Dictionary {
+    pub fn new<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut dictionary = Self {
+            words: Vec::new(),
+            nodes: vec![Node::default()],
+        };
+        for word in words {
+            dictionary.insert(word);
+        }
+        dictionary
+    }
+
+    /// Adds a word to the trie, empty and repeated words are ignored
+    pub fn insert(&mut self, word: &str) {
+        if word.is_empty() {
+            return;
+        }
+        let mut node = 0;
+        for letter in word.graphemes(true) {
+            node = match self.nodes[node].children.get(letter) {
+                Some(child) => *child,
+                None => {
+                    self.nodes.push(Node::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(letter.to_string(), child);
+                    child
+                }
+            };
+        }
+        if self.nodes[node].word.is_none() {
+            self.nodes[node].word = Some(self.words.len());
+            self.words.push(word.to_string());
+        }
+    }
+
+    /// Finds every occurrence of every word going in any of `directions`.
+    /// The result has the matches of each word at the same index as the word.
+    pub fn search<S: Space>(
+        &self,
+        space: &S,
+        directions: &[S::Position],
+    ) -> Vec<Vec<Line<S::Position>>> {
+        let mut matches = vec![Vec::new(); self.words.len()];
+        for start in space.positions() {
+            for (index, direction) in directions.iter().enumerate() {
+                self.walk(space, start, *direction, |word, end, length| {
+                    if index < effective_directions(length, directions).len() {
+                        matches[word].push(Line {
+                            start,
+                            end,
+                            direction: *direction,
+                        });
+                    }
+                });
+            }
+        }
+        matches
+    }
+
+    /// Follows the trie along the ray that starts at `start`, calling
+    /// `found` with each word that ends on the ray, where it ends and how
+    /// many letters it has
+    fn walk<S: Space>(
+        &self,
+        space: &S,
+        start: S::Position,
+        direction: S::Position,
+        mut found: impl FnMut(usize, S::Position, usize),
+    ) {
+        let mut node = 0;
+        let mut position = start;
+        let mut length = 0;
+        while let Some(letter) = space.letter(position) {
+            length += 1;
+            node = match self.nodes[node].children.get(letter) {
+                Some(child) => *child,
+                None => return,
+            };
+            if let Some(word) = self.nodes[node].word {
+                found(word, position, length);
+            }
+            position = S::step(position, direction);
+        }
+    }
+}
+
+/// One word per line, surrounding whitespace and blank lines are skipped
+impl From<String> for Dictionary {
+    fn from(value: String) -> Self {
+        Self::new(value.lines().map(str::trim))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TileMap, Vec2, SEARCH_DIRECTIONS};
+
+    fn find(grid: &str, word: &str) -> Vec<(Vec2, Vec2)> {
+        let map = TileMap::from(grid.to_string());
+        Dictionary::new([word]).search(&map, &SEARCH_DIRECTIONS)[0]
+            .iter()
+            .map(|found| (found.start, found.end))
+            .collect()
+    }
+
+    #[test]
+    fn repeated_letters_and_graphemes() {
+        // Palindromes read once from each end
+        assert_eq!(
+            find("LEVEL", "LEVEL"),
+            [
+                (Vec2::new(0, 0), Vec2::new(4, 0)),
+                (Vec2::new(4, 0), Vec2::new(0, 0))
+            ]
+        );
+        assert_eq!(
+            find("SASSAS", "SASS"),
+            [
+                (Vec2::new(0, 0), Vec2::new(3, 0)),
+                (Vec2::new(5, 0), Vec2::new(2, 0))
+            ]
+        );
+        // É written as an E and a combining accent takes a single tile
+        let grid = "CAFE\u{301}\nE\u{301}FAC";
+        assert_eq!(
+            find(grid, "CAFE\u{301}"),
+            [
+                (Vec2::new(0, 0), Vec2::new(3, 0)),
+                (Vec2::new(3, 1), Vec2::new(0, 1))
+            ]
+        );
+        assert_eq!(find(grid, "FE"), []);
+    }
+}
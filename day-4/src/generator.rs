@@ -0,0 +1,239 @@
+use std::fmt::Display;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{effective_directions, TileMap, Vec2};
+
+/// Letters used to fill the grid when there's nothing else to go on
+const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// Tries to place a word this many times before giving up on it
+const ATTEMPTS: usize = 1000;
+
+/// Xorshift generator, so the same seed always makes the same puzzle
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on 0
+        Self((seed ^ 0x2024_1204_dead_beef).max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Random number from 0 up to but not including `bound`
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// Settings for making a word search puzzle
+#[derive(Debug, Clone)]
+pub struct Generator {
+    pub width: usize,
+    pub height: usize,
+    /// Directions words can be placed in
+    pub directions: Vec<Vec2>,
+    /// Lets words share letters when they agree on them
+    pub overlaps: bool,
+    /// Lets words go off one edge and come back on the other
+    pub wrap: bool,
+    /// Letters for the cells no word goes through, empty uses the letters
+    /// of the words
+    pub distractors: Vec<String>,
+    pub seed: u64,
+}
+
+/// A generated grid and how many times each word can be found in it
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub grid: String,
+    pub words: Vec<String>,
+    /// How many times each word was placed on purpose
+    pub placed: Vec<usize>,
+    /// How many times each word reads in the finished grid in any of the
+    /// generator's directions, wrapping if it does, counting the ones the
+    /// distractors and overlaps made by accident
+    pub expected: Vec<usize>,
+}
+
+impl Generator {
+    /// Places every word once, in order, then fills the rest. Words that
+    /// don't fit anywhere after `ATTEMPTS` tries are left out, `placed` says
+    /// which ones.
+    pub fn generate(&self, words: &[String]) -> Puzzle {
+        let mut rng = Rng::new(self.seed);
+        let mut cells: Vec<Option<&str>> = vec![None; self.width * self.height];
+        let mut placed = vec![0; words.len()];
+        for (index, word) in words.iter().enumerate() {
+            let letters: Vec<&str> = word.graphemes(true).collect();
+            if letters.is_empty() || cells.is_empty() || self.directions.is_empty() {
+                continue;
+            }
+            for _ in 0..ATTEMPTS {
+                let start = Vec2::new(rng.below(self.width) as i64, rng.below(self.height) as i64);
+                let direction = self.directions[rng.below(self.directions.len())];
+                if let Some(positions) = self.fits(&cells, &letters, start, direction) {
+                    for (position, letter) in positions.into_iter().zip(&letters) {
+                        cells[position] = Some(letter);
+                    }
+                    placed[index] += 1;
+                    break;
+                }
+            }
+        }
+        let mut distractors: Vec<&str> = self.distractors.iter().map(String::as_str).collect();
+        if distractors.is_empty() {
+            distractors = words.iter().flat_map(|word| word.graphemes(true)).collect();
+        }
+        if distractors.is_empty() {
+            distractors = ALPHABET.graphemes(true).collect();
+        }
+        let grid = cells
+            .chunks(self.width.max(1))
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.unwrap_or_else(|| distractors[rng.below(distractors.len())]))
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let map = TileMap::from(grid.clone()).with_wrap(self.wrap);
+        let expected = words
+            .iter()
+            .map(|word| count_occurrences(&map, word, &self.directions))
+            .collect();
+        Puzzle {
+            grid,
+            words: words.to_vec(),
+            placed,
+            expected,
+        }
+    }
+
+    /// Indexes of the cells `letters` would take starting at `start`, if it
+    /// stays in the grid, or wraps around it, and only crosses cells it
+    /// agrees with
+    fn fits(
+        &self,
+        cells: &[Option<&str>],
+        letters: &[&str],
+        start: Vec2,
+        direction: Vec2,
+    ) -> Option<Vec<usize>> {
+        let positions: Vec<usize> = letters
+            .iter()
+            .enumerate()
+            .map(|(offset, letter)| {
+                let mut x = start.x + (offset as i64 * direction.x);
+                let mut y = start.y + (offset as i64 * direction.y);
+                if self.wrap {
+                    x = x.rem_euclid(self.width as i64);
+                    y = y.rem_euclid(self.height as i64);
+                }
+                if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+                    return None;
+                }
+                let position = y as usize * self.width + x as usize;
+                match cells[position] {
+                    None => Some(position),
+                    Some(other) if self.overlaps && other == *letter => Some(position),
+                    Some(_) => None,
+                }
+            })
+            .collect::<Option<Vec<usize>>>()?;
+        // Wrapping all the way around would put two letters in one cell
+        let mut distinct = positions.clone();
+        distinct.sort();
+        distinct.dedup();
+        (distinct.len() == positions.len()).then_some(positions)
+    }
+}
+
+/// Counts the occurrences of `word` going in `directions` the slow way,
+/// reading the letters of every ray out of every cell. Kept apart from the
+/// real search so it can check it.
+pub fn count_occurrences(map: &TileMap, word: &str, directions: &[Vec2]) -> usize {
+    let letters: Vec<&str> = word.graphemes(true).collect();
+    if letters.is_empty() {
+        return 0;
+    }
+    let directions = effective_directions(letters.len(), directions);
+    let mut count = 0;
+    for y in 0..map.height as i64 {
+        for x in 0..map.width as i64 {
+            for direction in directions {
+                let read: Option<Vec<&str>> = (0..letters.len() as i64)
+                    .map(|offset| {
+                        map.get(x + (offset * direction.x), y + (offset * direction.y))
+                            .map(|tile| tile.c.as_str())
+                    })
+                    .collect();
+                if read.is_some_and(|read| read == letters) {
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+impl Display for Puzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for ((word, placed), expected) in self.words.iter().zip(&self.placed).zip(&self.expected) {
+            writeln!(f, "{}: {} (placed {})", word, expected, placed)?;
+        }
+        write!(f, "{}", self.expected.iter().sum::<usize>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dictionary::Dictionary, Directions};
+
+    #[test]
+    fn search_finds_the_expected_counts() {
+        let mut rng = Rng::new(42);
+        let words: Vec<String> = ["XMAS", "SAMX", "MAM", "A", "XMASX", "ÅÄÖ"]
+            .map(str::to_string)
+            .to_vec();
+        let directions = [
+            Directions::All,
+            Directions::Forwards,
+            Directions::Knight,
+            Directions::Steps(vec![Vec2::new(3, -1), Vec2::new(-2, 0)]),
+        ];
+        for seed in 0..60 {
+            for directions in &directions {
+                let wrap = seed % 3 == 0;
+                let generator = Generator {
+                    width: rng.below(12) + 1,
+                    height: rng.below(12) + 1,
+                    directions: directions.vectors(),
+                    overlaps: seed % 2 == 0,
+                    wrap,
+                    distractors: Vec::new(),
+                    seed,
+                };
+                let puzzle = generator.generate(&words);
+                let map = TileMap::from(puzzle.grid.clone()).with_wrap(wrap);
+                let dictionary = Dictionary::new(words.iter().map(String::as_str));
+                let found: Vec<usize> = dictionary
+                    .search(&map, &generator.directions)
+                    .iter()
+                    .map(Vec::len)
+                    .collect();
+                assert_eq!(found, puzzle.expected, "{:?}\n{}", generator, puzzle.grid);
+                for (placed, expected) in puzzle.placed.iter().zip(&puzzle.expected) {
+                    assert!(placed <= expected);
+                }
+            }
+        }
+    }
+}
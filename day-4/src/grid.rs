@@ -0,0 +1,204 @@
+use std::fmt::Display;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{Error, Space, Tile};
+
+/// Position or step in a grid with `N` dimensions, x first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point<const N: usize>(pub [i64; N]);
+
+impl<const N: usize> Point<N> {
+    /// Every step to a neighbouring cell, 8 in 2D and 26 in 3D
+    pub fn directions() -> Vec<Self> {
+        let mut directions = vec![Self([0; N])];
+        for axis in 0..N {
+            directions = directions
+                .into_iter()
+                .flat_map(|point| {
+                    [-1, 0, 1].map(|step| {
+                        let mut point = point;
+                        point.0[axis] = step;
+                        point
+                    })
+                })
+                .collect();
+        }
+        directions.retain(|direction| direction.0 != [0; N]);
+        directions
+    }
+}
+
+impl<const N: usize> Display for Point<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "({})",
+            self.0
+                .iter()
+                .map(|coordinate| coordinate.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Letters laid out in `N` dimensions, stored with x changing fastest, then
+/// y, then z and so on
+#[derive(Debug, Clone)]
+pub struct Grid<const N: usize> {
+    pub tiles: Vec<Tile>,
+    pub size: [usize; N],
+    /// Going off one side comes back on the other, on every axis
+    pub wrap: bool,
+}
+
+impl<const N: usize> Grid<N> {
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    fn index(&self, point: Point<N>) -> Option<usize> {
+        let mut index = 0;
+        for axis in (0..N).rev() {
+            let size = self.size[axis] as i64;
+            let mut coordinate = point.0[axis];
+            if self.wrap && size > 0 {
+                coordinate = coordinate.rem_euclid(size);
+            }
+            if coordinate < 0 || coordinate >= size {
+                return None;
+            }
+            index = index * self.size[axis] + coordinate as usize;
+        }
+        Some(index)
+    }
+
+    pub fn get(&self, point: Point<N>) -> Option<&Tile> {
+        self.tiles.get(self.index(point)?)
+    }
+
+    /// The position of the tile at `index` in `tiles`
+    pub fn point(&self, index: usize) -> Point<N> {
+        let mut point = [0; N];
+        let mut rest = index;
+        for (coordinate, size) in point.iter_mut().zip(self.size) {
+            *coordinate = (rest % size) as i64;
+            rest /= size;
+        }
+        Point(point)
+    }
+}
+
+impl<const N: usize> Space for Grid<N> {
+    type Position = Point<N>;
+
+    fn positions(&self) -> impl Iterator<Item = Point<N>> + '_ {
+        (0..self.tiles.len()).map(|index| self.point(index))
+    }
+
+    fn letter(&self, position: Point<N>) -> Option<&str> {
+        self.get(position).map(|tile| tile.c.as_str())
+    }
+
+    fn step(position: Point<N>, direction: Point<N>) -> Point<N> {
+        let mut position = position;
+        for axis in 0..N {
+            position.0[axis] += direction.0[axis];
+        }
+        position
+    }
+}
+
+/// Layers of rows separated by blank lines, every layer has to be the same
+/// size as the first
+impl TryFrom<String> for Grid<3> {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut layers: Vec<Vec<&str>> = vec![Vec::new()];
+        for line in value.lines() {
+            if !line.trim().is_empty() {
+                layers.last_mut().unwrap().push(line);
+            } else if !layers.last().unwrap().is_empty() {
+                layers.push(Vec::new());
+            }
+        }
+        layers.retain(|layer| !layer.is_empty());
+        let height = layers.first().map_or(0, Vec::len);
+        let width = layers
+            .first()
+            .and_then(|layer| layer.first())
+            .map_or(0, |row| row.graphemes(true).count());
+        for (z, layer) in layers.iter().enumerate() {
+            let rows_fit = layer.len() == height;
+            let columns_fit = layer.iter().all(|row| row.graphemes(true).count() == width);
+            if !rows_fit || !columns_fit {
+                return Err(Error::RaggedLayer {
+                    layer: z,
+                    width,
+                    height,
+                });
+            }
+        }
+        let tiles = layers
+            .iter()
+            .flatten()
+            .flat_map(|row| row.graphemes(true).map(Tile::from))
+            .collect();
+        Ok(Self {
+            tiles,
+            size: [width, height, layers.len()],
+            wrap: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::Dictionary;
+
+    #[test]
+    fn searches_layers_in_26_directions() {
+        let directions = Point::<3>::directions();
+        assert_eq!(directions.len(), 26);
+        // XMAS going down through the layers, and along the diagonal of the
+        // whole cube
+        let input = "XQQQ\nQQQQ\nQQQQ\nQQQQ\n\n\
+                     MQQQ\nQMQQ\nQQQQ\nQQQQ\n\n\
+                     AQQQ\nQQQQ\nQQAQ\nQQQQ\n\n\
+                     SQQQ\nQQQQ\nQQQQ\nQQQS";
+        let grid = Grid::<3>::try_from(input.to_string()).unwrap();
+        assert_eq!(grid.size, [4, 4, 4]);
+        let matches = &Dictionary::new(["XMAS"]).search(&grid, &directions)[0];
+        let lines: Vec<(Point<3>, Point<3>, Point<3>)> = matches
+            .iter()
+            .map(|found| (found.start, found.end, found.direction))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (Point([0, 0, 0]), Point([0, 0, 3]), Point([0, 0, 1])),
+                (Point([0, 0, 0]), Point([3, 3, 3]), Point([1, 1, 1]))
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_ragged_layers() {
+        let error = Grid::<3>::try_from("AB\nCD\n\nEF\nG".to_string()).unwrap_err();
+        assert_eq!(
+            error,
+            Error::RaggedLayer {
+                layer: 1,
+                width: 2,
+                height: 2
+            }
+        );
+        assert_eq!(error.to_string(), "Layer 1 is not 2x2 like the first one");
+        let error = Grid::<3>::try_from("AB\n\nCD\nEF".to_string()).unwrap_err();
+        assert!(matches!(error, Error::RaggedLayer { layer: 1, .. }));
+    }
+}
@@ -0,0 +1,396 @@
+use std::{fmt::Display, str::FromStr};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+pub mod bitboard;
+pub mod dictionary;
+pub mod generator;
+pub mod grid;
+pub mod pattern;
+pub mod render;
+
+#[rustfmt::skip]
+pub const SEARCH_DIRECTIONS: [Vec2; 8] = [
+    Vec2 { x: -1, y: -1 }, Vec2 { x: 0, y: -1 }, Vec2 { x: 1, y: -1 },
+    Vec2 { x: -1, y: 0  }, /* --------------- */ Vec2 { x: 1, y: 0  },
+    Vec2 { x: -1, y: 1  }, Vec2 { x: 0, y: 1  }, Vec2 { x: 1, y: 1  },
+];
+
+#[rustfmt::skip]
+pub const X_DIRECTIONS: [Vec2; 4] = [
+    Vec2 { x: -1, y: -1 }, /* --------------- */ Vec2 { x: 1, y: -1 },
+    /* ---------------- */ /* --------------- */ /* --------------- */
+    Vec2 { x: -1, y: 1  }, /* --------------- */ Vec2 { x: 1, y: 1  },
+];
+
+/// The part of `directions` worth searching for a word `length` letters
+/// long. A single letter reads the same in every direction, so it only
+/// keeps the first one and is counted once.
+pub fn effective_directions<P>(length: usize, directions: &[P]) -> &[P] {
+    if length == 1 {
+        &directions[..directions.len().min(1)]
+    } else {
+        directions
+    }
+}
+
+/// Why a solver can't run with the options it was given
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The option or solver named only works on a single layer
+    SingleLayerOnly(&'static str),
+    /// A layer doesn't have as many rows or columns as the first one
+    RaggedLayer {
+        layer: usize,
+        width: usize,
+        height: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::SingleLayerOnly(what) => {
+                write!(f, "{} only works on inputs with a single layer", what)
+            }
+            Error::RaggedLayer {
+                layer,
+                width,
+                height,
+            } => write!(
+                f,
+                "Layer {} is not {}x{} like the first one",
+                layer, width, height
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Sets of directions that can be picked from the command line, by name
+/// or as a list of steps like `1,0;0,1`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directions {
+    /// All 8 directions
+    All,
+    /// Up, down, left and right
+    Orthogonal,
+    Diagonal,
+    /// Only the ones that read left to right or top to bottom, like in an
+    /// easy puzzle
+    Forwards,
+    /// The 8 jumps a chess knight can make
+    Knight,
+    Steps(Vec<Vec2>),
+}
+
+impl Directions {
+    pub fn vectors(&self) -> Vec<Vec2> {
+        match self {
+            Directions::Knight => [(1, 2), (2, 1), (2, -1), (1, -2)]
+                .into_iter()
+                .flat_map(|(x, y)| [Vec2::new(x, y), Vec2::new(-x, -y)])
+                .collect(),
+            Directions::Steps(steps) => steps.clone(),
+            _ => SEARCH_DIRECTIONS
+                .into_iter()
+                .filter(|direction| match self {
+                    Directions::Orthogonal => direction.x == 0 || direction.y == 0,
+                    Directions::Diagonal => direction.x != 0 && direction.y != 0,
+                    Directions::Forwards => {
+                        direction.x > 0 || (direction.x == 0 && direction.y > 0)
+                    }
+                    _ => true,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for Directions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => return Ok(Directions::All),
+            "orthogonal" => return Ok(Directions::Orthogonal),
+            "diagonal" => return Ok(Directions::Diagonal),
+            "forwards" => return Ok(Directions::Forwards),
+            "knight" => return Ok(Directions::Knight),
+            _ => {}
+        }
+        let steps = s
+            .split(';')
+            .map(|step| {
+                let parse = |n: &str| n.trim().parse::<i64>().ok();
+                let step = step
+                    .split_once(',')
+                    .and_then(|(x, y)| Some(Vec2::new(parse(x)?, parse(y)?)))
+                    .ok_or(format!("{:?} is not a direction set or a x,y step", step))?;
+                if step == Vec2::new(0, 0) {
+                    return Err("0,0 doesn't go anywhere".to_string());
+                }
+                Ok(step)
+            })
+            .collect::<Result<Vec<Vec2>, String>>()?;
+        Ok(Directions::Steps(steps))
+    }
+}
+
+#[derive(Debug)]
+pub struct TileMap {
+    pub tiles: Vec<Tile>,
+    pub width: usize,
+    pub height: usize,
+    /// Makes the grid a torus, going off one edge comes back on the other
+    pub wrap: bool,
+}
+
+impl TileMap {
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        let (x, y) = if self.wrap && self.width > 0 && self.height > 0 {
+            (
+                x.rem_euclid(self.width as i64),
+                y.rem_euclid(self.height as i64),
+            )
+        } else {
+            (x, y)
+        };
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        let x = x as usize;
+        let y = y as usize;
+        let y_offset = y * self.width;
+        Some(y_offset + x)
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Option<&Tile> {
+        self.tiles.get(self.index(x, y)?)
+    }
+
+    pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut Tile> {
+        let index = self.index(x, y)?;
+        self.tiles.get_mut(index)
+    }
+
+    /// Records match `id` on every tile `word` goes through
+    pub fn mark(&mut self, id: usize, word: &Word) {
+        let mut position = word.start;
+        loop {
+            if let Some(tile) = self.get_mut(position.x, position.y) {
+                tile.taken.push(id);
+            }
+            if position == word.end {
+                break;
+            }
+            position = Vec2::new(position.x + word.direction.x, position.y + word.direction.y);
+        }
+    }
+
+    /// Checks if the letters starting at x y and going in `direction` spell
+    /// out `search`
+    pub fn reads(&self, x: i64, y: i64, direction: Vec2, search: &[&str]) -> bool {
+        search.iter().enumerate().all(|(offset, letter)| {
+            let offset = offset as i64;
+            self.get(x + (offset * direction.x), y + (offset * direction.y))
+                .is_some_and(|tile| tile.c == *letter)
+        })
+    }
+
+    /// Checks if two words cross at x y, each going through it in one of
+    /// `directions`
+    pub fn find_exes(&self, x: i64, y: i64, search: &[&str], directions: &[Vec2]) -> Option<()> {
+        let search_length = search.len();
+        // Words with an even number of letters have no middle letter to
+        // cross on, shapes like that need a `Pattern`
+        if search_length.is_multiple_of(2) {
+            return None;
+        }
+        let half_point_in_word = (search_length / 2) as i64;
+        let mut found_line: Option<Vec2> = None;
+        for direction in directions {
+            let start_x = x - (half_point_in_word * direction.x);
+            let start_y = y - (half_point_in_word * direction.y);
+            if self.reads(start_x, start_y, *direction, search) {
+                // A word that reads the same backwards is found twice on the
+                // same line, that's not a cross
+                let line = if (direction.x, direction.y) < (0, 0) {
+                    Vec2::new(-direction.x, -direction.y)
+                } else {
+                    *direction
+                };
+                match found_line {
+                    Some(found_line) if found_line != line => return Some(()),
+                    _ => found_line = Some(line),
+                }
+            }
+        }
+        None
+    }
+}
+
+impl From<String> for TileMap {
+    fn from(value: String) -> Self {
+        let lines: Vec<&str> = value.lines().collect();
+        let width = lines.first().map_or(0, |line| line.graphemes(true).count());
+        let height = lines.len();
+        let tiles = lines
+            .iter()
+            .flat_map(|line| line.graphemes(true).map(Tile::from))
+            .collect();
+        Self {
+            tiles,
+            width,
+            height,
+            wrap: false,
+        }
+    }
+}
+
+impl Display for TileMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "TileMap {}x{}\n{}",
+            self.width,
+            self.height,
+            self.tiles
+                .chunks(self.width.max(1))
+                .map(|row| row
+                    .iter()
+                    .map(|tile| tile.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" "))
+                .collect::<Vec<String>>()
+                .join("\n\n")
+        )
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Tile {
+    /// A single grapheme, so letters made of more than one `char` still
+    /// take up one tile
+    pub c: String,
+    /// Ids of the matches that go through this tile
+    pub taken: Vec<usize>,
+}
+
+impl From<&str> for Tile {
+    fn from(value: &str) -> Self {
+        Self {
+            c: value.to_string(),
+            taken: Vec::new(),
+        }
+    }
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.c)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vec2 {
+    pub x: i64,
+    pub y: i64,
+}
+impl Vec2 {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Where a word was found, in any kind of `Space`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<P> {
+    pub start: P,
+    /// Where the last letter is, on a wrapping grid this can be off the
+    /// grid and has to be wrapped like the positions given to `get`
+    pub end: P,
+    pub direction: P,
+}
+
+/// A word found on a flat `TileMap`
+pub type Word = Line<Vec2>;
+
+/// Anything letters can be laid out in and searched along straight lines,
+/// so the same search works on a `TileMap` and on grids with more
+/// dimensions
+pub trait Space {
+    type Position: Copy + PartialEq;
+
+    /// Every cell that has a letter
+    fn positions(&self) -> impl Iterator<Item = Self::Position> + '_;
+    fn letter(&self, position: Self::Position) -> Option<&str>;
+    fn step(position: Self::Position, direction: Self::Position) -> Self::Position;
+}
+
+impl Space for TileMap {
+    type Position = Vec2;
+
+    fn positions(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.height as i64).flat_map(|y| (0..self.width as i64).map(move |x| Vec2::new(x, y)))
+    }
+
+    fn letter(&self, position: Vec2) -> Option<&str> {
+        self.get(position.x, position.y).map(|tile| tile.c.as_str())
+    }
+
+    fn step(position: Vec2, direction: Vec2) -> Vec2 {
+        Vec2::new(position.x + direction.x, position.y + direction.y)
+    }
+}
+
+impl Display for Vec2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl<P: Display> Display for Line<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {} going {}", self.start, self.end, self.direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letters(word: &str) -> Vec<&str> {
+        word.graphemes(true).collect()
+    }
+
+    #[test]
+    fn exes_across_the_edges() {
+        let grid = "AQQ\nQSS\nQMM".to_string();
+        let search = letters("MAS");
+        let map = TileMap::from(grid.clone());
+        assert_eq!(map.find_exes(0, 0, &search, &X_DIRECTIONS), None);
+        let map = TileMap::from(grid).with_wrap(true);
+        assert_eq!(map.find_exes(0, 0, &search, &X_DIRECTIONS), Some(()));
+        assert_eq!(map.find_exes(0, 0, &letters("MA"), &X_DIRECTIONS), None);
+    }
+
+    #[test]
+    fn exes_on_knight_moves() {
+        let grid = "QMQQQ\nMQQQQ\nQQAQQ\nQQQQS\nQQQSQ".to_string();
+        let search = letters("MAS");
+        let knight = Directions::Knight.vectors();
+        let map = TileMap::from(grid.clone());
+        assert_eq!(map.find_exes(2, 2, &search, &knight), Some(()));
+        assert_eq!(map.find_exes(2, 2, &search, &X_DIRECTIONS), None);
+        // Only one of the two words left
+        let map = TileMap::from(grid.replacen("MQQQQ", "QQQQQ", 1));
+        assert_eq!(map.find_exes(2, 2, &search, &knight), None);
+    }
+}
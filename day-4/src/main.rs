@@ -1,7 +1,16 @@
-use core::panic;
-use std::{collections::HashMap, fmt::Display};
+use std::fmt::Display;
 
 use clap::Parser;
+use day_4::{
+    bitboard::Bitboard,
+    dictionary::Dictionary,
+    generator::Generator,
+    grid::{Grid, Point},
+    pattern::Pattern,
+    render::render,
+    Directions, Error, Line, TileMap, Vec2, Word, SEARCH_DIRECTIONS, X_DIRECTIONS,
+};
+use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -12,257 +21,226 @@ struct Args {
     /// Solver
     #[arg(short, long, value_enum)]
     solver: Solver,
+    /// File with one word per line to look for instead of XMAS in Part1,
+    /// prints how many times each was found and where
+    #[arg(short, long)]
+    words: Option<std::path::PathBuf>,
+    /// File with a shape to look for instead of the X-MAS in Part2, counted
+    /// in every rotation and reflection
+    #[arg(long)]
+    pattern: Option<std::path::PathBuf>,
+    /// Prints the grid with every letter that isn't part of a match in
+    /// Part1 hidden, only for single layer inputs
+    #[arg(short, long)]
+    render: bool,
+    /// Where to write the grid made by the generator, printed with the
+    /// counts if not given
+    #[arg(short, long)]
+    output: Option<std::path::PathBuf>,
+    /// Width of the generated grid
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    width: u64,
+    /// Height of the generated grid
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    height: u64,
+    /// Directions to search and place words in: all, orthogonal, diagonal,
+    /// forwards, knight or steps like `1,0;0,1`. Part2 looks for crosses on
+    /// the diagonals by default, everything else uses all 8. Inputs with
+    /// several layers are always searched in all 26 directions and reject
+    /// this.
+    #[arg(short, long)]
+    directions: Option<Directions>,
+    /// Lets words go off one edge of the grid and come back on the other,
+    /// when searching and when generating
+    #[arg(long)]
+    wrap: bool,
+    /// Counts Part1 matches with bitboards instead of walking the grid,
+    /// for huge grids. Only counts, so it can't render or list matches.
+    #[arg(short, long, conflicts_with_all = ["wrap", "render"])]
+    fast: bool,
+    /// Threads for the fast solver, defaults to every available core
+    #[arg(short, long)]
+    threads: Option<usize>,
+    /// Seed for the generator, the same seed makes the same grid
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Keeps the generator from crossing words, even where they share a
+    /// letter
+    #[arg(long)]
+    no_overlaps: bool,
+    /// Letters to fill the generated grid with, defaults to the letters of
+    /// the words
+    #[arg(long)]
+    distractors: Option<String>,
+}
+
+/// Everything from the arguments that changes how the solvers search
+#[derive(Debug)]
+struct Options {
+    words: Option<Dictionary>,
+    pattern: Option<Pattern>,
+    render: bool,
+    directions: Option<Vec<Vec2>>,
+    wrap: bool,
+    /// Threads for the bitboard search, it's only used when set
+    fast: Option<usize>,
+    generator: Generator,
+    output: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, Clone, clap::ValueEnum)]
 enum Solver {
     Part1,
     Part2,
+    /// Makes a puzzle from the word list in `path`
+    Generate,
 }
 
-const SEARCH_WORD: &'static str = "XMAS";
-const SEARCH_X: &'static str = "MAS";
-
-#[rustfmt::skip]
-const SEARCH_DIRECTIONS: [Vec2; 8] = [
-    Vec2 { x: -1, y: -1 }, Vec2 { x: 0, y: -1 }, Vec2 { x: 1, y: -1 },
-    Vec2 { x: -1, y: 0  }, /* --------------- */ Vec2 { x: 1, y: 0  },
-    Vec2 { x: -1, y: 1  }, Vec2 { x: 0, y: 1  }, Vec2 { x: 1, y: 1  },
-];
-
-#[rustfmt::skip]
-const X_DIRECTIONS: [Vec2; 4] = [
-    Vec2 { x: -1, y: -1 }, /* --------------- */ Vec2 { x: 1, y: -1 },
-    /* ---------------- */ /* --------------- */ /* --------------- */
-    Vec2 { x: -1, y: 1  }, /* --------------- */ Vec2 { x: 1, y: 1  },
-];
-
-#[derive(Debug)]
-struct TileMap {
-    tiles: Vec<Tile>,
-    width: usize,
-    height: usize,
-}
-
-impl TileMap {
-    fn get(&self, x: i64, y: i64) -> Option<Tile> {
-        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
-            return None;
-        }
-        let x = x as usize;
-        let y = y as usize;
-        let y_offset = y * self.width;
-        self.tiles.get(y_offset + x).cloned()
-    }
+const SEARCH_WORD: &str = "XMAS";
+const SEARCH_X: &str = "MAS";
 
-    fn find_word(&self, x: i64, y: i64, search: &str) -> Option<Vec<Word>> {
-        let mut words = Vec::new();
-        let current_tile = self.get(x, y);
-        let current_tile = current_tile.as_ref()?; // return early if no letter at xy pos
-        let letter_index_in_word = search.find(|c| c == current_tile.c);
-        let letter_index_in_word = letter_index_in_word? as i64;
-        let start_offset = 0 - letter_index_in_word;
-        let end_offset = (search.len() as i64 - 1) - letter_index_in_word;
-        // println!("start_pos {} {} {:?}", x, y, current_tile.c);
-        for direction in SEARCH_DIRECTIONS {
-            let mut word = "".to_string();
-            for offset in start_offset..=end_offset {
-                let search_pos = Vec2::new(x + (offset * direction.x), y + (offset * direction.y));
-                // println!(
-                //     "search_pos {:?} {:?}",
-                //     search_pos,
-                //     self.get(search_pos.x, search_pos.y)
-                // );
-                let search_tile = self.get(search_pos.x, search_pos.y);
-                match search_tile {
-                    Some(tile) => {
-                        word.push(tile.c);
-                    }
-                    None => break, // no point in searching this direction anymore
+impl Solver {
+    pub fn solve(&self, input: String, options: &Options) -> Result<String, Error> {
+        // println!("{}", map);
+        let report = match self {
+            Solver::Part1 if is_layered(&input) => {
+                if options.render {
+                    return Err(Error::SingleLayerOnly("--render"));
                 }
-            }
-            if word == search {
-                let start = Vec2::new(
-                    x + (start_offset * direction.x),
-                    y + (start_offset * direction.y),
-                );
-                let end = Vec2::new(
-                    x + (end_offset * direction.x),
-                    y + (end_offset * direction.y),
-                );
-                words.push(Word {
-                    start,
-                    end,
-                    direction,
-                });
-            }
-        }
-        Some(words)
-    }
-
-    fn find_exes(&self, x: i64, y: i64, search: &str) -> Option<()> {
-        let search_length = search.len();
-        // if word to search has a even number of characters, it's not
-        // searchable as an x word
-        if search_length % 2 == 0 {
-            eprintln!("Cannot search of X shaped word with an even number of characters");
-            return None;
-        }
-        let half_point_in_word = (search.len() / 2) as i64;
-        let start_offset = 0 - half_point_in_word;
-        let end_offset = (search.len() as i64 - 1) - half_point_in_word;
-        let mut found_word = false;
-        for direction in X_DIRECTIONS {
-            let mut word = "".to_string();
-            for offset in start_offset..=end_offset {
-                let search_pos = Vec2::new(x + (offset * direction.x), y + (offset * direction.y));
-                let search_tile = self.get(search_pos.x, search_pos.y);
-                match search_tile {
-                    Some(tile) => {
-                        word.push(tile.c);
-                    }
-                    None => break, // no point in searching this direction anymore
+                if options.fast.is_some() {
+                    return Err(Error::SingleLayerOnly("--fast"));
+                }
+                if options.directions.is_some() {
+                    return Err(Error::SingleLayerOnly("--directions"));
                 }
+                let grid = Grid::<3>::try_from(input)?.with_wrap(options.wrap);
+                let default = Dictionary::new([SEARCH_WORD]);
+                let dictionary = options.words.as_ref().unwrap_or(&default);
+                let matches = dictionary.search(&grid, &Point::<3>::directions());
+                // There's no render for layers, so always say where they are
+                list_matches(dictionary, &matches, true)
             }
-            if word == search {
-                if found_word == true {
-                    return Some(());
+            Solver::Part1 if options.fast.is_some() => {
+                let threads = options.fast.unwrap();
+                let directions = options
+                    .directions
+                    .clone()
+                    .unwrap_or(SEARCH_DIRECTIONS.to_vec());
+                let default = Dictionary::new([SEARCH_WORD]);
+                let dictionary = options.words.as_ref().unwrap_or(&default);
+                let words: Vec<&str> = dictionary.words.iter().map(String::as_str).collect();
+                let bitboard = Bitboard::new(&input, &words);
+                let mut report = String::new();
+                let mut total = 0;
+                for word in words {
+                    let count = bitboard.count(word, &directions, threads);
+                    if options.words.is_some() {
+                        report.push_str(&format!("{}: {}\n", word, count));
+                    }
+                    total += count;
                 }
-                found_word = true;
+                report.push_str(&total.to_string());
+                report
             }
-        }
-        None
-    }
-}
-
-impl From<String> for TileMap {
-    fn from(value: String) -> Self {
-        let lines: Vec<&str> = value.lines().collect();
-        let width = { lines.first().unwrap().chars().count() };
-        let height = lines.iter().count();
-        let tiles = lines
-            .iter()
-            .flat_map(|line| line.chars().map(|c| Tile::from(c)))
-            .collect();
-        Self {
-            tiles,
-            width,
-            height,
-        }
-    }
-}
-
-impl Display for TileMap {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "TileMap {}x{}\n{}",
-            self.width,
-            self.height,
-            self.tiles
-                .chunks(self.width)
-                .map(|row| row
-                    .iter()
-                    .map(|tile| tile.to_string())
-                    .collect::<Vec<String>>()
-                    .join(" "))
-                .collect::<Vec<String>>()
-                .join("\n\n")
-        )
-    }
-}
-
-#[derive(Debug, Default, PartialEq, Eq, Clone)]
-struct Tile {
-    c: char,
-    taken: Vec<usize>,
-}
-
-impl From<char> for Tile {
-    fn from(value: char) -> Self {
-        Self {
-            c: value,
-            taken: Vec::new(),
-        }
-    }
-}
-
-impl ToString for Tile {
-    fn to_string(&self) -> String {
-        self.c.to_string()
-    }
-}
-
-// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-// enum Direction {
-//     Forwards,
-//     Reverse,
-// }
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Vec2 {
-    x: i64,
-    y: i64,
-}
-impl Vec2 {
-    fn new(x: i64, y: i64) -> Self {
-        Self { x, y }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Word {
-    start: Vec2,
-    end: Vec2,
-    direction: Vec2,
-}
-
-impl Solver {
-    pub fn solve(&self, input: String) -> String {
-        let map = TileMap::from(input);
-        // println!("{}", map);
-        match self {
             Solver::Part1 => {
-                let mut words: Vec<Word> = Vec::new();
-                for y in 0..map.height {
-                    for x in 0..map.width {
-                        let found_words = map.find_word(x as i64, y as i64, SEARCH_WORD);
-                        // todo!("{:?}", found_words);
-                        if found_words.is_none() {
-                            continue;
-                        }
-                        let found_words = found_words.unwrap();
-                        let found_words: Vec<Word> = found_words
-                            .iter()
-                            .cloned()
-                            .filter(|found_word| {
-                                words.iter().find(|word| found_word == *word).is_none()
-                            })
-                            .collect();
-                        if !found_words.is_empty() {
-                            words.extend(found_words);
-                        }
+                let mut map = TileMap::from(input).with_wrap(options.wrap);
+                let directions = options
+                    .directions
+                    .clone()
+                    .unwrap_or(SEARCH_DIRECTIONS.to_vec());
+                let default = Dictionary::new([SEARCH_WORD]);
+                let dictionary = options.words.as_ref().unwrap_or(&default);
+                let matches = dictionary.search(&map, &directions);
+                let mut report = String::new();
+                if options.render {
+                    let matches: Vec<(usize, Word)> = matches
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(word, found)| found.iter().map(move |found| (word, *found)))
+                        .collect();
+                    for (id, (_, word)) in matches.iter().enumerate() {
+                        map.mark(id, word);
                     }
+                    report.push_str(&render(&map, &matches, &directions));
                 }
-                words.len().to_string()
+                report.push_str(&list_matches(dictionary, &matches, options.words.is_some()));
+                report
             }
             Solver::Part2 => {
+                if is_layered(&input) {
+                    return Err(Error::SingleLayerOnly("Part2"));
+                }
+                let map = TileMap::from(input).with_wrap(options.wrap);
+                let directions = options.directions.clone().unwrap_or(X_DIRECTIONS.to_vec());
+                if let Some(pattern) = &options.pattern {
+                    let counts = pattern.count(&map);
+                    let mut report = String::new();
+                    for (orientation, count) in &counts {
+                        report.push_str(&format!("{}: {}\n", orientation, count));
+                    }
+                    let total: usize = counts.iter().map(|(_, count)| count).sum();
+                    report.push_str(&total.to_string());
+                    return Ok(report);
+                }
+                let search: Vec<&str> = SEARCH_X.graphemes(true).collect();
                 let mut xes = 0;
                 for y in 0..map.height {
                     for x in 0..map.width {
-                        let found_xes = map.find_exes(x as i64, y as i64, SEARCH_X);
+                        let found_xes = map.find_exes(x as i64, y as i64, &search, &directions);
                         // todo!("{:?}", found_words);
                         if found_xes.is_none() {
                             continue;
                         }
-                        let found_xes = found_xes.unwrap();
                         xes += 1;
                     }
                 }
                 xes.to_string()
             }
+            Solver::Generate => {
+                let words: Vec<String> = input
+                    .lines()
+                    .map(str::trim)
+                    .filter(|word| !word.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                let puzzle = options.generator.generate(&words);
+                match &options.output {
+                    Some(output) => {
+                        std::fs::write(output, format!("{}\n", puzzle.grid)).unwrap();
+                        puzzle.to_string()
+                    }
+                    None => format!("{}\n\n{}", puzzle.grid, puzzle),
+                }
+            }
+        };
+        Ok(report)
+    }
+}
+
+/// Inputs with blank lines between rows are stacks of layers
+fn is_layered(input: &str) -> bool {
+    input.trim().lines().any(|line| line.trim().is_empty())
+}
+
+/// The total number of matches, after how many times each word was found
+/// and where if `verbose`
+fn list_matches<P: Display>(
+    dictionary: &Dictionary,
+    matches: &[Vec<Line<P>>],
+    verbose: bool,
+) -> String {
+    let mut report = String::new();
+    if verbose {
+        for (word, found) in dictionary.words.iter().zip(matches) {
+            report.push_str(&format!("{}: {}\n", word, found.len()));
+            for found in found {
+                report.push_str(&format!("  {}\n", found));
+            }
         }
     }
+    let total: usize = matches.iter().map(Vec::len).sum();
+    report.push_str(&total.to_string());
+    report
 }
 
 fn main() {
@@ -275,6 +253,45 @@ fn main() {
     } else {
         println!("Ommitting long contents");
     }
-    let result = args.solver.solve(contents);
+    let options = Options {
+        words: args
+            .words
+            .map(|path| Dictionary::from(std::fs::read_to_string(path).unwrap())),
+        pattern: args
+            .pattern
+            .map(|path| Pattern::from(std::fs::read_to_string(path).unwrap())),
+        render: args.render,
+        directions: args.directions.as_ref().map(Directions::vectors),
+        wrap: args.wrap,
+        fast: args.fast.then(|| {
+            args.threads.unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|threads| threads.get())
+                    .unwrap_or(1)
+            })
+        }),
+        generator: Generator {
+            width: args.width as usize,
+            height: args.height as usize,
+            directions: args.directions.unwrap_or(Directions::All).vectors(),
+            overlaps: !args.no_overlaps,
+            wrap: args.wrap,
+            distractors: args
+                .distractors
+                .map(|letters| letters.graphemes(true).map(str::to_string).collect())
+                .unwrap_or_default(),
+            seed: args.seed,
+        },
+        output: args.output,
+    };
+    let result = args
+        .solver
+        .solve(contents, &options)
+        .unwrap_or_else(|error| exit_with(error));
     println!("{}", result);
 }
+
+fn exit_with(error: Error) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
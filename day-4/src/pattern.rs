@@ -0,0 +1,178 @@
+use std::fmt::Display;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{TileMap, Vec2};
+
+/// Matches any letter, but there has to be a letter
+pub const WILDCARD: &str = ".";
+/// Not part of the pattern, anything goes there even the edge of the grid
+pub const NOTHING: &str = " ";
+
+/// One of the 8 ways a pattern can be laid on the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Orientation {
+    /// Quarter turns clockwise
+    pub turns: u8,
+    /// Flipped left to right before turning
+    pub mirrored: bool,
+}
+
+impl Orientation {
+    pub fn all() -> impl Iterator<Item = Self> {
+        [false, true]
+            .into_iter()
+            .flat_map(|mirrored| (0..4).map(move |turns| Self { turns, mirrored }))
+    }
+
+    fn apply(&self, offset: Vec2) -> Vec2 {
+        let mut offset = offset;
+        if self.mirrored {
+            offset = Vec2::new(-offset.x, offset.y);
+        }
+        for _ in 0..self.turns {
+            offset = Vec2::new(-offset.y, offset.x);
+        }
+        offset
+    }
+}
+
+impl Display for Orientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "turned {}°", self.turns as u32 * 90)?;
+        if self.mirrored {
+            write!(f, " mirrored")?;
+        }
+        Ok(())
+    }
+}
+
+/// A small grid of letters to stamp on the map, written like the map itself
+/// with `.` for any letter and spaces for cells that don't matter
+///
+/// ```text
+/// M.S
+/// .A.
+/// M.S
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// Offset of every cell that has to match and its letter, `None` for
+    /// wildcards
+    pub cells: Vec<(Vec2, Option<String>)>,
+}
+
+impl Pattern {
+    /// The pattern laid out in `orientation`, moved so no offset is negative
+    /// and sorted so equal layouts compare equal
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        let mut cells: Vec<(Vec2, Option<String>)> = self
+            .cells
+            .iter()
+            .map(|(offset, letter)| (orientation.apply(*offset), letter.clone()))
+            .collect();
+        let min_x = cells.iter().map(|(offset, _)| offset.x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|(offset, _)| offset.y).min().unwrap_or(0);
+        for (offset, _) in cells.iter_mut() {
+            *offset = Vec2::new(offset.x - min_x, offset.y - min_y);
+        }
+        cells.sort_by_key(|(offset, _)| (offset.y, offset.x));
+        Self { cells }
+    }
+
+    /// Every distinct layout of the pattern, symmetric patterns have fewer
+    /// than 8 so the same match isn't counted twice
+    pub fn orientations(&self) -> Vec<(Orientation, Pattern)> {
+        let mut orientations: Vec<(Orientation, Pattern)> = Vec::new();
+        for orientation in Orientation::all() {
+            let oriented = self.oriented(orientation);
+            if orientations.iter().all(|(_, other)| *other != oriented) {
+                orientations.push((orientation, oriented));
+            }
+        }
+        orientations
+    }
+
+    /// Checks if the pattern, as it is, fits with its top left corner at x y
+    pub fn matches_at(&self, map: &TileMap, x: i64, y: i64) -> bool {
+        self.cells.iter().all(|(offset, letter)| {
+            map.get(x + offset.x, y + offset.y)
+                .is_some_and(|tile| letter.as_ref().is_none_or(|letter| tile.c == *letter))
+        })
+    }
+
+    /// Counts the matches of each distinct orientation over the whole map
+    pub fn count(&self, map: &TileMap) -> Vec<(Orientation, usize)> {
+        if self.cells.is_empty() {
+            return Vec::new();
+        }
+        self.orientations()
+            .into_iter()
+            .map(|(orientation, oriented)| {
+                let mut count = 0;
+                for y in 0..map.height as i64 {
+                    for x in 0..map.width as i64 {
+                        if oriented.matches_at(map, x, y) {
+                            count += 1;
+                        }
+                    }
+                }
+                (orientation, count)
+            })
+            .collect()
+    }
+}
+
+impl From<String> for Pattern {
+    fn from(value: String) -> Self {
+        let cells = value
+            .lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.graphemes(true)
+                    .enumerate()
+                    .filter(|(_, letter)| *letter != NOTHING)
+                    .map(move |(x, letter)| {
+                        let letter = (letter != WILDCARD).then(|| letter.to_string());
+                        (Vec2::new(x as i64, y as i64), letter)
+                    })
+            })
+            .collect();
+        Self { cells }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../data/input.txt");
+
+    fn pattern(text: &str) -> Pattern {
+        Pattern::from(text.to_string())
+    }
+
+    #[test]
+    fn symmetric_patterns_have_fewer_orientations() {
+        assert_eq!(pattern("A").orientations().len(), 1);
+        assert_eq!(pattern("MAS").orientations().len(), 4);
+        assert_eq!(pattern("M S\n A \nM S").orientations().len(), 4);
+        assert_eq!(pattern(" M \nMAS\n S ").orientations().len(), 4);
+        assert_eq!(pattern("M\nA\nSS").orientations().len(), 8);
+        for (_, oriented) in pattern(" M \nMAS\n S ").orientations() {
+            assert!(oriented
+                .cells
+                .iter()
+                .all(|(offset, _)| offset.x >= 0 && offset.y >= 0));
+        }
+    }
+
+    #[test]
+    fn counts_the_x_mas_example() {
+        let map = TileMap::from(EXAMPLE.to_string());
+        let counts = pattern(include_str!("../data/x-mas.txt")).count(&map);
+        assert_eq!(counts.len(), 4);
+        assert_eq!(counts.iter().map(|(_, count)| count).sum::<usize>(), 9);
+        assert!(Pattern { cells: Vec::new() }.count(&map).is_empty());
+    }
+}
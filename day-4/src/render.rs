@@ -0,0 +1,95 @@
+use crate::{TileMap, Vec2, Word};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+/// Swaps the foreground and background for cells shared by several matches
+const SHARED: &str = "\x1b[7m";
+const COLORS: [&str; 12] = [
+    "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m", "\x1b[91m", "\x1b[92m",
+    "\x1b[93m", "\x1b[94m", "\x1b[95m", "\x1b[96m",
+];
+/// Shown instead of letters that aren't part of any match
+const UNMATCHED: &str = ".";
+
+/// Color for a match, every word and direction pair gets its own as long
+/// as there are enough colors. `directions` are the ones that were searched.
+fn color(word: usize, found: &Word, directions: &[Vec2]) -> &'static str {
+    let direction = directions
+        .iter()
+        .position(|direction| *direction == found.direction)
+        .unwrap_or(0);
+    COLORS[(word * directions.len() + direction) % COLORS.len()]
+}
+
+/// Draws the grid for a terminal after the matches have been marked with
+/// `TileMap::mark`. `matches` has the word index and match for each id,
+/// found going in `directions`. Each tile gets the color of the first match
+/// through it.
+pub fn render(map: &TileMap, matches: &[(usize, Word)], directions: &[Vec2]) -> String {
+    let mut output = String::new();
+    for row in map.tiles.chunks(map.width.max(1)) {
+        for tile in row {
+            let Some(first) = tile.taken.first() else {
+                output.push_str(DIM);
+                output.push_str(UNMATCHED);
+                output.push_str(RESET);
+                continue;
+            };
+            let (word, found) = &matches[*first];
+            output.push_str(color(*word, found, directions));
+            if tile.taken.len() > 1 {
+                output.push_str(SHARED);
+            }
+            output.push_str(&tile.c);
+            output.push_str(RESET);
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_and_renders_shared_cells() {
+        let mut map = TileMap::from("XMAS\nAQQQ".to_string());
+        let directions = [Vec2::new(1, 0), Vec2::new(0, 1)];
+        let matches = [
+            (
+                0,
+                Word {
+                    start: Vec2::new(0, 0),
+                    end: Vec2::new(3, 0),
+                    direction: directions[0],
+                },
+            ),
+            (
+                1,
+                Word {
+                    start: Vec2::new(0, 0),
+                    end: Vec2::new(0, 1),
+                    direction: directions[1],
+                },
+            ),
+        ];
+        for (id, (_, word)) in matches.iter().enumerate() {
+            map.mark(id, word);
+        }
+        assert_eq!(map.get(0, 0).unwrap().taken, [0, 1]);
+        assert_eq!(map.get(3, 0).unwrap().taken, [0]);
+        assert_eq!(map.get(0, 1).unwrap().taken, [1]);
+        assert!(map.get(1, 1).unwrap().taken.is_empty());
+
+        let first = COLORS[0];
+        // Second word going in the second direction
+        let second = COLORS[3];
+        let dot = format!("{}.{}", DIM, RESET);
+        let expected = format!(
+            "{first}{SHARED}X{RESET}{first}M{RESET}{first}A{RESET}{first}S{RESET}\n\
+             {second}A{RESET}{dot}{dot}{dot}\n"
+        );
+        assert_eq!(render(&map, &matches, &directions), expected);
+    }
+}
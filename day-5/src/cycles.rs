@@ -0,0 +1,249 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+
+use serde::{ser::SerializeStruct, Serialize};
+
+use crate::{OrderRule, OrderRuleList};
+
+/// Pages whose rules go around in a circle, so no order can follow all of
+/// them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// Each page has to come before the next one, and the last one before
+    /// the first
+    pub pages: Vec<i64>,
+}
+
+impl Cycle {
+    /// The rules that make up the cycle, in order
+    pub fn rules(&self) -> Vec<OrderRule> {
+        self.pages
+            .iter()
+            .zip(self.pages.iter().cycle().skip(1))
+            .map(|(left, right)| OrderRule((*left, *right)))
+            .collect()
+    }
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self
+            .pages
+            .iter()
+            .chain(self.pages.first())
+            .map(|page| page.to_string())
+            .collect::<Vec<String>>()
+            .join(" -> ");
+        let rules = self
+            .rules()
+            .iter()
+            .map(|OrderRule((left, right))| format!("{}|{}", left, right))
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{} ({})", path, rules)
+    }
+}
+
+/// The pages and the rules as `left|right`, like they are printed
+impl Serialize for Cycle {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let rules: Vec<String> = self
+            .rules()
+            .iter()
+            .map(|OrderRule((left, right))| format!("{}|{}", left, right))
+            .collect();
+        let mut state = serializer.serialize_struct("Cycle", 2)?;
+        state.serialize_field("pages", &self.pages)?;
+        state.serialize_field("rules", &rules)?;
+        state.end()
+    }
+}
+
+impl OrderRuleList {
+    /// Cycles in all the rules, enough of them that every rule that is part
+    /// of a cycle shows up in one. Real inputs can have these and still be
+    /// fine, as long as no single update has all the pages of a cycle.
+    pub fn cycles(&self) -> Vec<Cycle> {
+        let mut pages: Vec<i64> = self
+            .rules
+            .iter()
+            .flat_map(|OrderRule((left, right))| [*left, *right])
+            .collect();
+        pages.sort();
+        pages.dedup();
+        self.cycles_in(&pages)
+    }
+
+    /// Cycles in the rules between `pages`, found per strongly connected
+    /// group. Empty when the pages can be ordered.
+    pub fn cycles_in(&self, pages: &[i64]) -> Vec<Cycle> {
+        let positions: HashMap<i64, usize> = pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| (*page, index))
+            .collect();
+        let successors: Vec<Vec<usize>> = pages
+            .iter()
+            .map(|page| {
+                let mut later: Vec<usize> = self
+                    .after(*page)
+                    .filter_map(|later| positions.get(&later).copied())
+                    .collect();
+                later.sort();
+                later
+            })
+            .collect();
+        strongly_connected(&successors)
+            .into_iter()
+            .flat_map(|component| covering_cycles(&successors, &component))
+            .map(|cycle| {
+                let mut pages: Vec<i64> = cycle.into_iter().map(|index| pages[index]).collect();
+                // Start from the lowest page so the same cycle always looks
+                // the same, whatever order the update has
+                let lowest = (0..pages.len()).min_by_key(|index| pages[*index]).unwrap();
+                pages.rotate_left(lowest);
+                Cycle { pages }
+            })
+            .collect()
+    }
+}
+
+/// Tarjan's algorithm, with an explicit stack so long chains of rules
+/// can't overflow the real one
+struct Tarjan<'a> {
+    successors: &'a [Vec<usize>],
+    order: Vec<Option<usize>>,
+    lowest: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    visited: usize,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn enter(&mut self, node: usize) {
+        self.order[node] = Some(self.visited);
+        self.lowest[node] = self.visited;
+        self.visited += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+    }
+
+    fn visit(&mut self, root: usize) {
+        self.enter(root);
+        // Node and how many of its successors were already looked at
+        let mut work = vec![(root, 0)];
+        while let Some((node, child)) = work.last_mut() {
+            let node = *node;
+            if let Some(next) = self.successors[node].get(*child).copied() {
+                *child += 1;
+                match self.order[next] {
+                    None => {
+                        self.enter(next);
+                        work.push((next, 0));
+                    }
+                    Some(order) if self.on_stack[next] => {
+                        self.lowest[node] = self.lowest[node].min(order);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+            work.pop();
+            if let Some((parent, _)) = work.last() {
+                self.lowest[*parent] = self.lowest[*parent].min(self.lowest[node]);
+            }
+            if Some(self.lowest[node]) == self.order[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort();
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+/// Groups of nodes that can all reach each other, as indexes into
+/// `successors`
+fn strongly_connected(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut tarjan = Tarjan {
+        successors,
+        order: vec![None; successors.len()],
+        lowest: vec![0; successors.len()],
+        on_stack: vec![false; successors.len()],
+        stack: Vec::new(),
+        visited: 0,
+        components: Vec::new(),
+    };
+    for root in 0..successors.len() {
+        if tarjan.order[root].is_none() {
+            tarjan.visit(root);
+        }
+    }
+    tarjan.components.sort();
+    tarjan.components
+}
+
+/// Cycles that between them use every rule inside `component`, each one as
+/// short as it can be. Empty if it's a single node without a rule to itself.
+fn covering_cycles(successors: &[Vec<usize>], component: &[usize]) -> Vec<Vec<usize>> {
+    let mut cycles: Vec<Vec<usize>> = Vec::new();
+    let mut covered: HashSet<(usize, usize)> = HashSet::new();
+    for left in component {
+        for right in successors[*left]
+            .iter()
+            .filter(|right| component.contains(right))
+        {
+            if covered.contains(&(*left, *right)) {
+                continue;
+            }
+            // Going through the rule and then back the shortest way
+            let Some(back) = shortest_path(successors, component, *right, *left) else {
+                continue;
+            };
+            let mut cycle = vec![*left];
+            cycle.extend(&back[..back.len() - 1]);
+            for (from, to) in cycle.iter().zip(cycle.iter().cycle().skip(1)) {
+                covered.insert((*from, *to));
+            }
+            cycles.push(cycle);
+        }
+    }
+    cycles
+}
+
+/// Nodes from `from` to `to`, both included, going only through `component`
+fn shortest_path(
+    successors: &[Vec<usize>],
+    component: &[usize],
+    from: usize,
+    to: usize,
+) -> Option<Vec<usize>> {
+    let mut parents: HashMap<usize, usize> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(node) = queue.pop_front() {
+        if node == to {
+            let mut path = vec![to];
+            while let Some(parent) = parents.get(path.last().unwrap()) {
+                path.push(*parent);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in &successors[node] {
+            if *next != from && component.contains(next) && !parents.contains_key(next) {
+                parents.insert(*next, node);
+                queue.push_back(*next);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{cycles::Cycle, Error, OrderRuleList, Pages};
+
+/// Page lists longer than this are wrapped when printed
+const PAGES_PER_LINE: usize = 20;
+
+/// A rule an update breaks, positions count from 1
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    pub left: i64,
+    pub right: i64,
+    pub left_position: usize,
+    pub right_position: usize,
+}
+
+/// Everything about why an update is or isn't in the right order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    /// Which update this is, counting from 1
+    pub update: usize,
+    pub pages: Pages,
+    pub valid: bool,
+    pub violations: Vec<Violation>,
+    /// The order the pages should be in, missing if it's already right or
+    /// if there's no right order
+    pub corrected: Option<Pages>,
+    /// Why there's no right order
+    pub cycles: Vec<Cycle>,
+}
+
+impl Explanation {
+    pub fn new(rules: &OrderRuleList, update: usize, pages: &[i64]) -> Self {
+        let mut violations = Vec::new();
+        for (left_position, left) in pages.iter().enumerate() {
+            for (right_position, right) in pages.iter().enumerate().take(left_position) {
+                if rules.must_precede(*left, *right) {
+                    violations.push(Violation {
+                        left: *left,
+                        right: *right,
+                        left_position: left_position + 1,
+                        right_position: right_position + 1,
+                    });
+                }
+            }
+        }
+        violations.sort_by_key(|violation| (violation.right_position, violation.left_position));
+        let valid = violations.is_empty();
+        let (corrected, cycles) = if valid {
+            (None, Vec::new())
+        } else {
+            match rules.sort(pages) {
+                Ok(corrected) => (Some(corrected), Vec::new()),
+                Err(Error::Inconsistent { cycles, .. }) => (None, cycles),
+                Err(_) => (None, Vec::new()),
+            }
+        };
+        Self {
+            update,
+            pages: pages.to_vec(),
+            valid,
+            violations,
+            corrected,
+            cycles,
+        }
+    }
+}
+
+/// Comma separated pages, broken into indented lines when there are many
+fn wrap_pages(pages: &[i64], indent: usize) -> String {
+    pages
+        .chunks(PAGES_PER_LINE)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|page| page.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        })
+        .collect::<Vec<String>>()
+        .join(&format!(",\n{}", " ".repeat(indent)))
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Update {} ({} pages): ", self.update, self.pages.len())?;
+        if self.valid {
+            write!(f, "valid")?;
+        } else {
+            write!(f, "invalid, breaks {} rules", self.violations.len())?;
+        }
+        write!(f, "\n  pages:     {}", wrap_pages(&self.pages, 13))?;
+        let width = self
+            .violations
+            .iter()
+            .map(|violation| format!("{}|{}", violation.left, violation.right).len())
+            .max()
+            .unwrap_or(0);
+        for violation in &self.violations {
+            let rule = format!("{}|{}", violation.left, violation.right);
+            write!(
+                f,
+                "\n  {:<width$}  {} at #{} comes after {} at #{}",
+                rule,
+                violation.left,
+                violation.left_position,
+                violation.right,
+                violation.right_position,
+                width = width
+            )?;
+        }
+        if let Some(corrected) = &self.corrected {
+            write!(f, "\n  corrected: {}", wrap_pages(corrected, 13))?;
+        }
+        for cycle in &self.cycles {
+            write!(f, "\n  cycle:     {}", cycle)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{OrderRule, OrderRuleList};
+
+/// Diagram languages the graph can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Graphviz
+    Dot,
+    Mermaid,
+}
+
+/// `left` has to be printed before `right`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub left: i64,
+    pub right: i64,
+    /// The update the graph was made for prints `right` first
+    pub broken: bool,
+}
+
+/// The rules as pages connected by arrows, ready to be drawn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    pub pages: Vec<i64>,
+    pub edges: Vec<Edge>,
+}
+
+impl OrderRuleList {
+    /// Graph of every rule, or only of the rules between the pages of
+    /// `update` with the ones it breaks marked. With `reduce` rules that
+    /// follow from other rules are left out, except broken ones.
+    pub fn graph(&self, update: Option<&[i64]>, reduce: bool) -> Graph {
+        let pages: Vec<i64> = match update {
+            Some(update) => update.to_vec(),
+            None => {
+                let mut pages: Vec<i64> = self
+                    .rules
+                    .iter()
+                    .flat_map(|OrderRule((left, right))| [*left, *right])
+                    .collect();
+                pages.sort();
+                pages.dedup();
+                pages
+            }
+        };
+        let positions: HashMap<i64, usize> = pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| (*page, index))
+            .collect();
+        let mut edges: Vec<Edge> = self
+            .rules
+            .iter()
+            .filter_map(|OrderRule((left, right))| {
+                let left_position = positions.get(left)?;
+                let right_position = positions.get(right)?;
+                Some(Edge {
+                    left: *left,
+                    right: *right,
+                    broken: update.is_some() && right_position < left_position,
+                })
+            })
+            .collect();
+        edges.sort_by_key(|edge| (positions[&edge.left], positions[&edge.right]));
+        edges.dedup();
+        if reduce {
+            edges = transitive_reduction(edges);
+        }
+        Graph { pages, edges }
+    }
+}
+
+/// Drops every edge whose pages are still connected some other way. Edges
+/// are dropped one at a time against what's left, so even with cycles
+/// every page can still reach the same pages as before.
+fn transitive_reduction(mut edges: Vec<Edge>) -> Vec<Edge> {
+    let mut index = 0;
+    while index < edges.len() {
+        let edge = edges[index];
+        if !edge.broken && has_other_path(&edges, index) {
+            edges.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+    edges
+}
+
+/// Checks if the ends of `edges[skip]` are connected without using it
+fn has_other_path(edges: &[Edge], skip: usize) -> bool {
+    let Edge { left, right, .. } = edges[skip];
+    let mut seen: HashSet<i64> = HashSet::from([left]);
+    let mut queue = VecDeque::from([left]);
+    while let Some(page) = queue.pop_front() {
+        for (index, edge) in edges.iter().enumerate() {
+            if index == skip || edge.left != page {
+                continue;
+            }
+            if edge.right == right {
+                return true;
+            }
+            if seen.insert(edge.right) {
+                queue.push_back(edge.right);
+            }
+        }
+    }
+    false
+}
+
+impl Graph {
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Dot => self.to_dot(),
+            Format::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph rules {\n");
+        for page in &self.pages {
+            dot.push_str(&format!("    {};\n", page));
+        }
+        for edge in &self.edges {
+            let style = if edge.broken { " [color=red]" } else { "" };
+            dot.push_str(&format!("    {} -> {}{};\n", edge.left, edge.right, style));
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Mermaid ids can't be bare numbers, so pages are named `p47` and
+    /// labelled `47`
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("graph LR\n");
+        for page in &self.pages {
+            mermaid.push_str(&format!("    p{}[{}]\n", page, page));
+        }
+        for edge in &self.edges {
+            mermaid.push_str(&format!("    p{} --> p{}\n", edge.left, edge.right));
+        }
+        for (index, edge) in self.edges.iter().enumerate() {
+            if edge.broken {
+                mermaid.push_str(&format!("    linkStyle {} stroke:red\n", index));
+            }
+        }
+        mermaid.trim_end().to_string()
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+
+use cycles::Cycle;
+
+pub mod cycles;
+pub mod explain;
+pub mod graph;
+
+pub type Pages = Vec<i64>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The rules between the pages of an update go around in circles, so
+    /// there's no right order
+    Inconsistent { pages: Pages, cycles: Vec<Cycle> },
+    /// Asked for an update, counting from 1, that isn't in the input
+    NoSuchUpdate(usize),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Inconsistent { pages, cycles } => {
+                write!(
+                    f,
+                    "Update {:?} can't be ordered, its rules have cycles:",
+                    pages
+                )?;
+                for cycle in cycles {
+                    write!(f, "\n  {}", cycle)?;
+                }
+                Ok(())
+            }
+            Error::NoSuchUpdate(update) => write!(f, "There's no update number {}", update),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// `left|right`, the left page has to be printed before the right one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OrderRule(pub (i64, i64));
+
+/// Every rule from the input, indexed by the page that has to go first so
+/// checking a pair of pages doesn't need to look through all of them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderRuleList {
+    pub rules: Vec<OrderRule>,
+    /// Pages that have to come after each page
+    after: HashMap<i64, HashSet<i64>>,
+}
+
+impl OrderRuleList {
+    pub fn new(rules: Vec<OrderRule>) -> Self {
+        let mut after: HashMap<i64, HashSet<i64>> = HashMap::new();
+        for OrderRule((left, right)) in &rules {
+            after.entry(*left).or_default().insert(*right);
+        }
+        Self { rules, after }
+    }
+
+    /// Checks if there's a rule saying `left` has to come before `right`
+    pub fn must_precede(&self, left: i64, right: i64) -> bool {
+        self.after
+            .get(&left)
+            .is_some_and(|after| after.contains(&right))
+    }
+
+    /// Pages that have to come after `page`
+    pub fn after(&self, page: i64) -> impl Iterator<Item = i64> + '_ {
+        self.after.get(&page).into_iter().flatten().copied()
+    }
+
+    /// Checks that no page comes after one it has to be printed before.
+    /// Each page is checked against whichever is smaller, the pages before
+    /// it or the pages it has to precede, so this is never worse than
+    /// quadratic in the size of the update, nor than one lookup for each
+    /// rule that starts at one of its pages.
+    pub fn is_ordered(&self, pages: &[i64]) -> bool {
+        let mut seen: HashSet<i64> = HashSet::with_capacity(pages.len());
+        for page in pages {
+            if let Some(after) = self.after.get(page) {
+                let broken = if after.len() < seen.len() {
+                    after.iter().any(|later| seen.contains(later))
+                } else {
+                    seen.iter().any(|earlier| after.contains(earlier))
+                };
+                if broken {
+                    return false;
+                }
+            }
+            seen.insert(*page);
+        }
+        true
+    }
+
+    /// Puts the pages in an order every rule between them agrees with, by
+    /// topologically sorting the rules that only involve these pages. Pages
+    /// that no rule orders keep their relative order. Fails with the cycles
+    /// if the rules can't all be followed.
+    pub fn sort(&self, pages: &[i64]) -> Result<Pages, Error> {
+        let positions: HashMap<i64, usize> = pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| (*page, index))
+            .collect();
+        let successors: Vec<Vec<usize>> = pages
+            .iter()
+            .map(|page| {
+                // Sorted so ties always come out in the same order
+                let mut later: Vec<usize> = self
+                    .after(*page)
+                    .filter_map(|later| positions.get(&later).copied())
+                    .collect();
+                later.sort();
+                later
+            })
+            .collect();
+        let mut before_count = vec![0; pages.len()];
+        for later in successors.iter().flatten() {
+            before_count[*later] += 1;
+        }
+        let mut ready: VecDeque<usize> = (0..pages.len())
+            .filter(|index| before_count[*index] == 0)
+            .collect();
+        let mut sorted = Vec::with_capacity(pages.len());
+        while let Some(index) = ready.pop_front() {
+            sorted.push(pages[index]);
+            for later in &successors[index] {
+                before_count[*later] -= 1;
+                if before_count[*later] == 0 {
+                    ready.push_back(*later);
+                }
+            }
+        }
+        if sorted.len() < pages.len() {
+            return Err(Error::Inconsistent {
+                pages: pages.to_vec(),
+                cycles: self.cycles_in(pages),
+            });
+        }
+        Ok(sorted)
+    }
+}
+
+impl From<String> for OrderRuleList {
+    fn from(value: String) -> Self {
+        Self::new(
+            value
+                .lines()
+                .filter(|line| line.contains("|"))
+                .filter_map(|pair| pair.split_once("|"))
+                .map(|(left, right)| OrderRule((left.parse().unwrap(), right.parse().unwrap())))
+                .collect(),
+        )
+    }
+}
+
+impl Display for OrderRuleList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.rules
+                .iter()
+                .map(|OrderRule((left, right))| format!("{}|{}", left, right))
+                .collect::<Vec<String>>()
+                .join("\n")
+        )
+    }
+}
+
+/// Every line of comma separated pages after the rules
+pub fn parse_updates(input: &str) -> Vec<Pages> {
+    input
+        .lines()
+        .filter(|line| !line.contains("|") && !line.is_empty())
+        .map(|line| line.split(",").map(|page| page.parse().unwrap()).collect())
+        .collect()
+}
+
+/// The page in the middle of an update
+pub fn middle_page(pages: &[i64]) -> Option<i64> {
+    pages.get(pages.len() / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use explain::{Explanation, Violation};
+    use graph::Format;
+
+    const EXAMPLE: &str = "47|53\n97|13\n97|61\n97|47\n75|29\n61|13\n75|53\n29|13\n97|29\n\
+        53|29\n61|53\n97|53\n61|29\n47|13\n75|47\n97|75\n47|61\n75|61\n47|29\n75|13\n53|13\n\
+        \n75,47,61,53,29\n97,61,53,29,13\n75,29,13\n75,97,47,61,53\n61,13,29\n97,13,75,29,47\n";
+
+    fn rules(rules: &[(i64, i64)]) -> OrderRuleList {
+        OrderRuleList::new(rules.iter().map(|rule| OrderRule(*rule)).collect())
+    }
+
+    /// Stream of pseudo-random numbers that only depends on `seed`
+    fn numbers(seed: u64) -> impl FnMut() -> u64 {
+        let mut state = seed;
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        }
+    }
+
+    #[test]
+    fn example() {
+        let rules = OrderRuleList::from(EXAMPLE.to_string());
+        let updates = parse_updates(EXAMPLE);
+        let part1: i64 = updates
+            .iter()
+            .filter(|pages| rules.is_ordered(pages))
+            .filter_map(|pages| middle_page(pages))
+            .sum();
+        assert_eq!(part1, 143);
+        let part2: i64 = updates
+            .iter()
+            .filter(|pages| !rules.is_ordered(pages))
+            .map(|pages| middle_page(&rules.sort(pages).unwrap()).unwrap())
+            .sum();
+        assert_eq!(part2, 123);
+        assert_eq!(
+            rules.sort(&[97, 13, 75, 29, 47]),
+            Ok(vec![97, 75, 47, 29, 13])
+        );
+    }
+
+    #[test]
+    fn is_ordered_checks_every_pair() {
+        // Page 0 has to come before a lot of pages, the rest are random
+        let mut next = numbers(0x2024_1205);
+        let mut pairs: Vec<(i64, i64)> = (1..=300).map(|page| (0, page)).collect();
+        for _ in 0..400 {
+            let left = (next() % 40) as i64 + 1;
+            let right = (next() % 40) as i64 + 1;
+            if left != right {
+                pairs.push((left, right));
+            }
+        }
+        let rules = rules(&pairs);
+        for _ in 0..2000 {
+            let mut pages: Vec<i64> = (0..=40).collect();
+            let length = (next() % 8) as usize + 2;
+            for index in 0..length {
+                let other = index + (next() as usize % (pages.len() - index));
+                pages.swap(index, other);
+            }
+            pages.truncate(length);
+            let expected = (0..pages.len()).all(|right| {
+                (right + 1..pages.len()).all(|left| !rules.must_precede(pages[left], pages[right]))
+            });
+            assert_eq!(rules.is_ordered(&pages), expected, "{:?}", pages);
+        }
+    }
+
+    #[test]
+    fn cycles_make_updates_inconsistent() {
+        let example = OrderRuleList::from(EXAMPLE.to_string());
+        assert!(example.cycles().is_empty());
+        let rules = rules(&[(1, 2), (2, 3), (3, 1), (3, 4)]);
+        let error = rules.sort(&[2, 4, 3, 1]).unwrap_err();
+        let Error::Inconsistent { pages, cycles } = &error else {
+            panic!("{:?}", error);
+        };
+        assert_eq!(pages, &vec![2, 4, 3, 1]);
+        assert_eq!(
+            cycles,
+            &vec![Cycle {
+                pages: vec![1, 2, 3]
+            }]
+        );
+        assert_eq!(cycles[0].to_string(), "1 -> 2 -> 3 -> 1 (1|2, 2|3, 3|1)");
+        // Without all the pages of the cycle it can be ordered
+        assert_eq!(rules.sort(&[4, 3, 1]), Ok(vec![3, 4, 1]));
+    }
+
+    #[test]
+    fn self_rule_is_a_cycle() {
+        let rules = rules(&[(5, 5), (5, 6)]);
+        let cycle = Cycle { pages: vec![5] };
+        assert_eq!(rules.cycles(), vec![cycle.clone()]);
+        assert_eq!(
+            rules.sort(&[6, 5]),
+            Err(Error::Inconsistent {
+                pages: vec![6, 5],
+                cycles: vec![cycle]
+            })
+        );
+        assert_eq!(rules.sort(&[6]), Ok(vec![6]));
+    }
+
+    #[test]
+    fn cycles_cover_every_rule_in_them() {
+        // Two loops sharing page 2, and a long chain that has none
+        let mut pairs = vec![(1, 2), (2, 1), (2, 3), (3, 2), (3, 10)];
+        pairs.extend((10..100_000).map(|page| (page, page + 1)));
+        let rules = rules(&pairs);
+        let cycles = rules.cycles();
+        assert_eq!(
+            cycles,
+            vec![Cycle { pages: vec![1, 2] }, Cycle { pages: vec![2, 3] }]
+        );
+        let covered: HashSet<OrderRule> = cycles.iter().flat_map(Cycle::rules).collect();
+        assert_eq!(covered.len(), 4);
+    }
+
+    #[test]
+    fn reduction_of_a_total_order_is_a_chain() {
+        let pairs: Vec<(i64, i64)> = (1..=6)
+            .flat_map(|left| (left + 1..=6).map(move |right| (left, right)))
+            .collect();
+        let rules = rules(&pairs);
+        assert_eq!(rules.graph(None, false).edges.len(), 15);
+        let reduced = rules.graph(None, true);
+        let edges: Vec<(i64, i64)> = reduced
+            .edges
+            .iter()
+            .map(|edge| (edge.left, edge.right))
+            .collect();
+        assert_eq!(edges, vec![(1, 2), (2, 3), (3, 4), (4, 5), (5, 6)]);
+        // Broken rules are kept even when other rules imply them
+        let reduced = rules.graph(Some(&[1, 2, 4, 3]), true);
+        let broken: Vec<(i64, i64)> = reduced
+            .edges
+            .iter()
+            .filter(|edge| edge.broken)
+            .map(|edge| (edge.left, edge.right))
+            .collect();
+        assert_eq!(broken, vec![(3, 4)]);
+    }
+
+    #[test]
+    fn draws_broken_rules_in_red() {
+        let rules = rules(&[(1, 2), (2, 3), (1, 3)]);
+        let graph = rules.graph(Some(&[1, 3, 2]), false);
+        assert_eq!(
+            graph.to_dot(),
+            "digraph rules {\n    1;\n    3;\n    2;\n    1 -> 3;\n    1 -> 2;\n    \
+             2 -> 3 [color=red];\n}"
+        );
+        assert_eq!(
+            graph.to_mermaid(),
+            "graph LR\n    p1[1]\n    p3[3]\n    p2[2]\n    p1 --> p3\n    p1 --> p2\n    \
+             p2 --> p3\n    linkStyle 2 stroke:red"
+        );
+        // 1|3 follows from the others, the broken rule moves up to index 1
+        let reduced = rules.graph(Some(&[1, 3, 2]), true);
+        assert_eq!(
+            reduced.render(Format::Mermaid),
+            "graph LR\n    p1[1]\n    p3[3]\n    p2[2]\n    p1 --> p2\n    p2 --> p3\n    \
+             linkStyle 1 stroke:red"
+        );
+    }
+
+    #[test]
+    fn explains_updates() {
+        let rules = OrderRuleList::from(EXAMPLE.to_string());
+        let explanation = Explanation::new(&rules, 4, &[75, 97, 47, 61, 53]);
+        assert!(!explanation.valid);
+        assert_eq!(
+            explanation.violations,
+            vec![Violation {
+                left: 97,
+                right: 75,
+                left_position: 2,
+                right_position: 1
+            }]
+        );
+        assert_eq!(explanation.corrected, Some(vec![97, 75, 47, 61, 53]));
+        let explanation = Explanation::new(&rules, 1, &[75, 47, 61, 53, 29]);
+        assert!(explanation.valid);
+        assert_eq!(explanation.corrected, None);
+        let rules = self::rules(&[(1, 2), (2, 1)]);
+        let explanation = Explanation::new(&rules, 1, &[1, 2]);
+        assert_eq!(explanation.corrected, None);
+        assert_eq!(explanation.cycles, vec![Cycle { pages: vec![1, 2] }]);
+    }
+}
@@ -1,4 +1,7 @@
 use clap::Parser;
+use day_5::{
+    explain::Explanation, graph::Format, middle_page, parse_updates, Error, OrderRuleList,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about)]
@@ -9,168 +12,76 @@ struct Args {
     /// Solver
     #[arg(short, long, value_enum)]
     solver: Solver,
+    /// Lists the cycles in all the rules and in the rules of each update
+    #[arg(short, long)]
+    cycles: bool,
+    /// Language the graph is written in
+    #[arg(short, long, value_enum, default_value_t = Format::Dot)]
+    format: Format,
+    /// Only graphs the pages of this update, counting from 1, with the
+    /// rules it breaks in red
+    #[arg(short, long)]
+    update: Option<usize>,
+    /// Hides rules that follow from other rules
+    #[arg(short, long)]
+    reduce: bool,
+    /// Explains what's wrong with each update, or with the one picked with
+    /// --update, and how to fix it
+    #[arg(short, long)]
+    explain: bool,
+    /// Prints the explanations as JSON
+    #[arg(long)]
+    json: bool,
 }
 
 #[derive(Debug, Clone, clap::ValueEnum)]
 enum Solver {
     Part1,
     Part2,
+    /// Draws the rules as a graph
+    Graph,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct OrderRule((i64, i64));
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct OrderRuleList(Vec<OrderRule>);
-
-impl From<String> for OrderRuleList {
-    fn from(value: String) -> Self {
-        Self(
-            value
-                .lines()
-                .filter(|line| line.contains("|"))
-                .map(|pair| pair.split_once("|"))
-                .filter_map(|pair| pair)
-                .map(|(left, right)| OrderRule((left.parse().unwrap(), right.parse().unwrap())))
-                .collect(),
-        )
-    }
+/// How the graph solver draws the rules
+#[derive(Debug, Clone)]
+struct GraphOptions {
+    format: Format,
+    update: Option<usize>,
+    reduce: bool,
 }
 
-impl ToString for OrderRuleList {
-    fn to_string(&self) -> String {
-        self.0
-            .iter()
-            .map(|OrderRule((left, right))| format!("{}|{}", left, right))
-            .collect::<Vec<String>>()
-            .join("\n")
-    }
-}
-
-type Pages = Vec<i64>;
-
 impl Solver {
-    pub fn solve(&self, input: String) -> String {
+    pub fn solve(&self, input: String, graph: &GraphOptions) -> Result<String, Error> {
+        let rules = OrderRuleList::from(input.clone());
+        let page_lists = parse_updates(&input);
         match self {
-            Solver::Part1 => {
-                let mut result = 0;
-                let rules = OrderRuleList::from(input.clone());
-                // println!("{}", rules.to_string());
-                let page_lists: Vec<Pages> = input
-                    .lines()
-                    .filter(|line| !line.contains("|") && !line.is_empty())
-                    .map(|line| line.split(",").map(|page| page.parse().unwrap()).collect())
-                    .collect();
-                for page_list in page_lists {
-                    // println!(
-                    //     "{}",
-                    //     pages
-                    //         .iter()
-                    //         .map(|page| page.to_string())
-                    //         .collect::<Vec<String>>()
-                    //         .join(",")
-                    // );
-                    println!("Pages {:?}", page_list);
-                    let mut correct = true;
-                    for (index, page) in page_list.iter().enumerate() {
-                        for (other_index, other_page) in page_list.iter().enumerate() {
-                            if page == other_page {
-                                continue;
-                            }
-                            if index > other_index {
-                                continue;
-                            }
-                            println!("Comparing {} with {}", page, other_page);
-                            for OrderRule((left, right)) in rules.0.iter() {
-                                if page == left && other_page == right {
-                                    // We are good
-                                    println!("- Rule {} -> {}", left, right);
-                                    println!("- Correct Order");
-                                }
-                                if page == right && other_page == left {
-                                    correct = false;
-
-                                    println!("- Rule {} -> {}", left, right);
-                                    println!("- Incorrect Order");
-                                    break;
-                                }
-                            }
-                            if !correct {
-                                break;
-                            }
-                        }
-                    }
-                    if correct {
-                        // Get middle number
-                        let index = page_list.len() / 2;
-                        println!("Getting {} for page {:?}", index, page_list);
-                        let middle_page = page_list.get(index);
-                        if let Some(page) = middle_page {
-                            result += page;
-                        }
-                    }
-                }
-
-                result.to_string()
-            }
+            Solver::Part1 => Ok(page_lists
+                .iter()
+                .filter(|page_list| rules.is_ordered(page_list))
+                .filter_map(|page_list| middle_page(page_list))
+                .sum::<i64>()
+                .to_string()),
             Solver::Part2 => {
                 let mut result = 0;
-                let rules = OrderRuleList::from(input.clone());
-                // println!("{}", rules.to_string());
-                let page_lists: Vec<Pages> = input
-                    .lines()
-                    .filter(|line| !line.contains("|") && !line.is_empty())
-                    .map(|line| line.split(",").map(|page| page.parse().unwrap()).collect())
-                    .collect();
-                for page_list in page_lists {
-                    // println!(
-                    //     "{}",
-                    //     pages
-                    //         .iter()
-                    //         .map(|page| page.to_string())
-                    //         .collect::<Vec<String>>()
-                    //         .join(",")
-                    // );
-                    println!("Pages {:?}", page_list);
-                    let mut correct = true;
-                    for (index, page) in page_list.iter().enumerate() {
-                        for (other_index, other_page) in page_list.iter().enumerate() {
-                            if page == other_page {
-                                continue;
-                            }
-                            if index > other_index {
-                                continue;
-                            }
-                            println!("Comparing {} with {}", page, other_page);
-                            for OrderRule((left, right)) in rules.0.iter() {
-                                if page == left && other_page == right {
-                                    // We are good
-                                    println!("- Rule {} -> {}", left, right);
-                                    println!("- Correct Order");
-                                }
-                                if page == right && other_page == left {
-                                    correct = false;
-
-                                    println!("- Rule {} -> {}", left, right);
-                                    println!("- Incorrect Order");
-                                    break;
-                                }
-                            }
-                            if !correct {
-                                break;
-                            }
-                        }
-                    }
-                    if correct {
-                        // Get middle number
-                        let index = page_list.len() / 2;
-                        println!("Getting {} for page {:?}", index, page_list);
-                        let middle_page = page_list.get(index);
-                        if let Some(page) = middle_page {
-                            result += page;
-                        }
-                    }
+                for page_list in page_lists
+                    .iter()
+                    .filter(|page_list| !rules.is_ordered(page_list))
+                {
+                    result += middle_page(&rules.sort(page_list)?).unwrap_or(0);
                 }
-                result.to_string()
+                Ok(result.to_string())
+            }
+            Solver::Graph => {
+                let update = match graph.update {
+                    Some(update) => Some(
+                        page_lists
+                            .get(update.wrapping_sub(1))
+                            .ok_or(Error::NoSuchUpdate(update))?
+                            .as_slice(),
+                    ),
+                    None => None,
+                };
+                Ok(rules.graph(update, graph.reduce).render(graph.format))
             }
         }
     }
@@ -181,7 +92,70 @@ fn main() {
     let input = args.path;
     println!("{:?}", input);
     let contents = std::fs::read_to_string(input).unwrap();
-    println!("{}", contents);
-    let result = args.solver.solve(contents);
+    if contents.lines().count() < 64 {
+        println!("{}", contents);
+    } else {
+        println!("Ommitting long contents");
+    }
+    if args.cycles {
+        print_cycles(&contents);
+    }
+    if args.explain || args.json {
+        let rules = OrderRuleList::from(contents.clone());
+        let explanations: Vec<Explanation> = parse_updates(&contents)
+            .iter()
+            .enumerate()
+            .map(|(index, pages)| (index + 1, pages))
+            .filter(|(update, _)| args.update.is_none_or(|picked| picked == *update))
+            .map(|(update, pages)| Explanation::new(&rules, update, pages))
+            .collect();
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&explanations).unwrap());
+        } else {
+            for explanation in explanations {
+                println!("{}", explanation);
+            }
+        }
+    }
+    let result = args
+        .solver
+        .solve(
+            contents,
+            &GraphOptions {
+                format: args.format,
+                update: args.update,
+                reduce: args.reduce,
+            },
+        )
+        .unwrap_or_else(|error| exit_with(error));
     println!("{}", result);
 }
+
+fn print_cycles(input: &str) {
+    let rules = OrderRuleList::from(input.to_string());
+    let cycles = rules.cycles();
+    println!("{} cycles in all the rules", cycles.len());
+    for cycle in cycles {
+        println!("  {}", cycle);
+    }
+    for (index, page_list) in parse_updates(input).iter().enumerate() {
+        let cycles = rules.cycles_in(page_list);
+        if cycles.is_empty() {
+            continue;
+        }
+        println!(
+            "Update {} {:?} has {} cycles",
+            index + 1,
+            page_list,
+            cycles.len()
+        );
+        for cycle in cycles {
+            println!("  {}", cycle);
+        }
+    }
+}
+
+fn exit_with(error: Error) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
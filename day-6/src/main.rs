@@ -60,10 +60,10 @@ impl From<String> for TileMap {
     fn from(value: String) -> Self {
         let lines: Vec<&str> = value.lines().collect();
         let width = { lines.first().unwrap().chars().count() };
-        let height = lines.iter().count();
+        let height = lines.len();
         let tiles = lines
             .iter()
-            .flat_map(|line| line.chars().map(|c| Tile::from(c)))
+            .flat_map(|line| line.chars().map(Tile::from))
             .collect();
         let guard_char_index = lines
             .join("")
@@ -151,16 +151,16 @@ impl From<char> for Guard {
     }
 }
 
-impl ToString for Guard {
-    fn to_string(&self) -> String {
-        match self.looking_at {
+impl Display for Guard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self.looking_at {
             Vec2 { x: 0, y: -1 } => '^',
             Vec2 { x: 1, y: 0 } => '>',
             Vec2 { x: 0, y: 1 } => 'v',
             Vec2 { x: -1, y: 0 } => '<',
             _ => todo!(),
-        }
-        .to_string()
+        };
+        write!(f, "{}", c)
     }
 }
 
@@ -177,13 +177,13 @@ impl From<char> for Tile {
     }
 }
 
-impl ToString for Tile {
-    fn to_string(&self) -> String {
-        match self.obstacle {
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self.obstacle {
             true => '#',
             false => '.',
-        }
-        .to_string()
+        };
+        write!(f, "{}", c)
     }
 }
 
@@ -239,24 +239,22 @@ impl Solver {
                 // to the list
                 // Create a map of all spots
                 let spots_to_check = (0..map.height)
-                    .map(|y| {
+                    .flat_map(|y| {
                         (0..map.width)
                             .map(|x| (x as i64, y as i64))
                             .collect::<Vec<(i64, i64)>>()
                     })
-                    .flatten()
                     .map(|(x, y)| Vec2::new(x, y))
                     // Get all the spots that are not occupied with an obstacle
                     .filter(|pos| {
                         !map.get(pos.x, pos.y)
-                            .expect(format!("Could not get tile at pos: {:?}", pos).as_str())
+                            .unwrap_or_else(|| panic!("Could not get tile at pos: {:?}", pos))
                             .obstacle
                     })
                     .filter(|pos| &map.guard.position != pos)
                     .collect::<Vec<Vec2>>();
                 let mut loop_spots = Vec::new();
-                let mut iter = spots_to_check.iter();
-                while let Some(spot_to_check) = iter.next() {
+                for spot_to_check in spots_to_check.iter() {
                     let mut visited_positions = Vec::new();
                     println!("Checking for position: {:?}", spot_to_check);
                     let mut map = map.clone();
@@ -300,17 +298,16 @@ impl Solver {
                 // to the list
                 // Create a map of all spots
                 let spots_to_check = (0..map.height)
-                    .map(|y| {
+                    .flat_map(|y| {
                         (0..map.width)
                             .map(|x| (x as i64, y as i64))
                             .collect::<Vec<(i64, i64)>>()
                     })
-                    .flatten()
                     .map(|(x, y)| Vec2::new(x, y))
                     // Get all the spots that are not occupied with an obstacle
                     .filter(|pos| {
                         !map.get(pos.x, pos.y)
-                            .expect(format!("Could not get tile at pos: {:?}", pos).as_str())
+                            .unwrap_or_else(|| panic!("Could not get tile at pos: {:?}", pos))
                             .obstacle
                     })
                     .filter(|pos| &map.guard.position != pos)
@@ -323,7 +320,7 @@ impl Solver {
                 // by +1 to make sure we don't have an extra chunk with 1 element
                 let chunks = spots_to_check
                     .chunks(spots_count / thread_count + 1)
-                    .map(|chunk| Vec::from(chunk));
+                    .map(Vec::from);
                 for (thread_index, chunk) in chunks.enumerate() {
                     // Clone variables to use inside thread
                     let map = map.clone();
@@ -332,8 +329,7 @@ impl Solver {
                     thread::spawn(move || {
                         println!("Thead {}", thread_index);
                         let mut loop_spots: Vec<Vec2> = Vec::new();
-                        let mut iter = chunk.iter();
-                        while let Some(spot_to_check) = iter.next() {
+                        for spot_to_check in chunk.iter() {
                             println!("Checking for position: {:?}", spot_to_check);
                             let mut map = map.clone();
                             {
@@ -358,7 +354,7 @@ impl Solver {
                                         .push((map.guard.position, map.guard.looking_at));
                                 } else {
                                     println!("This looped");
-                                    loop_spots.push(spot_to_check.clone());
+                                    loop_spots.push(*spot_to_check);
                                     break;
                                 }
                                 if tile_in_front.obstacle {